@@ -0,0 +1,35 @@
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+#[derive(Facet)]
+struct Transform {
+    name: String,
+    matrix: [f32; 16],
+}
+
+fn main() {
+    let identity = [
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 1.0, 0.0, //
+        0.0, 0.0, 0.0, 1.0,
+    ];
+
+    let mut translated = identity;
+    translated[3] = 2.0;
+    translated[7] = -1.0;
+
+    let a = Transform {
+        name: "camera".into(),
+        matrix: identity,
+    };
+    let b = Transform {
+        name: "camera".into(),
+        matrix: translated,
+    };
+    println!("{}", a.diff(&b));
+
+    let from: &[u32] = &[1, 2, 3];
+    let to: &[u32] = &[1, 4, 3, 5];
+    println!("{}", from.diff(&to));
+}
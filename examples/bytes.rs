@@ -0,0 +1,27 @@
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+#[derive(Facet)]
+struct Frame {
+    id: u32,
+    payload: Vec<u8>,
+}
+
+fn main() {
+    let a = Frame {
+        id: 7,
+        payload: b"GET /index.html HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n".to_vec(),
+    };
+    let b = Frame {
+        id: 7,
+        payload:
+            b"GET /index.htm HTTP/1.1\r\nHost: example.org\r\nAccept: */*\r\nX-Trace: 1\r\n\r\n"
+                .to_vec(),
+    };
+    println!("{}", a.diff(&b));
+
+    let header: [u8; 8] = [0xca, 0xfe, 0xba, 0xbe, 0x00, 0x01, 0x02, 0x03];
+    let mut patched = header;
+    patched[5] = 0xff;
+    println!("{}", header.diff(&patched));
+}
@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+// A loosely typed document, e.g. parsed from JSON
+#[derive(Facet)]
+#[repr(C)]
+#[facet(untagged)]
+#[allow(dead_code)]
+enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+#[derive(Facet)]
+struct Server {
+    host: String,
+    port: u16,
+    tls: bool,
+}
+
+fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Object(
+        entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+fn main() {
+    let old = object([
+        ("host", Value::String("localhost".into())),
+        ("port", Value::Number(8080.0)),
+        (
+            "tags",
+            Value::Array(vec![Value::String("dev".into()), Value::Null]),
+        ),
+    ]);
+    let new = object([
+        ("host", Value::String("example.com".into())),
+        ("port", Value::Number(8080.0)),
+        (
+            "tags",
+            Value::Array(vec![Value::String("prod".into()), Value::Null]),
+        ),
+        ("tls", Value::Bool(true)),
+    ]);
+
+    // Two documents
+    println!("{}", old.diff(&new));
+
+    // A typed struct against a document, e.g. to validate a migration
+    let server = Server {
+        host: "example.com".into(),
+        port: 8080,
+        tls: false,
+    };
+    println!("{}", server.diff(&new));
+}
@@ -25,6 +25,13 @@ enum MaybeUpdate {
     Add(Option<usize>),
 }
 
+#[derive(Facet)]
+#[repr(C)]
+enum State {
+    Running { pid: u32, started: u64 },
+    Stopped { pid: u32, started: u64, code: i32 },
+}
+
 fn main() {
     let a = Update::Remove(0);
     let b = Update::Remove(1);
@@ -61,4 +68,17 @@ fn main() {
 
     diff = g.diff(&h);
     println!("{diff}");
+
+    let running = State::Running {
+        pid: 42,
+        started: 1_700_000_000,
+    };
+    let stopped = State::Stopped {
+        pid: 42,
+        started: 1_700_000_000,
+        code: 1,
+    };
+
+    let diff = running.diff(&stopped);
+    println!("{diff}");
 }
@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+#[derive(Facet)]
+struct Mesh {
+    name: String,
+    vertices: u32,
+}
+
+#[derive(Facet)]
+struct Node {
+    name: String,
+    mesh: Rc<Mesh>,
+}
+
+#[derive(Facet)]
+struct Scene {
+    nodes: Vec<Node>,
+}
+
+fn main() {
+    let tree = Rc::new(Mesh {
+        name: "tree".into(),
+        vertices: 1200,
+    });
+    let detailed_tree = Rc::new(Mesh {
+        name: "tree".into(),
+        vertices: 4800,
+    });
+
+    // Both nodes share the same mesh, so it is only compared once
+    let a = Scene {
+        nodes: vec![
+            Node {
+                name: "oak".into(),
+                mesh: tree.clone(),
+            },
+            Node {
+                name: "elm".into(),
+                mesh: tree,
+            },
+        ],
+    };
+    let b = Scene {
+        nodes: vec![
+            Node {
+                name: "oak".into(),
+                mesh: detailed_tree.clone(),
+            },
+            Node {
+                name: "elm".into(),
+                mesh: detailed_tree,
+            },
+        ],
+    };
+
+    println!("{}", a.diff(&b));
+}
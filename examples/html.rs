@@ -0,0 +1,49 @@
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+#[derive(Facet)]
+struct Config {
+    name: String,
+    replicas: u32,
+    image: Image,
+    ports: Vec<u16>,
+    labels: Vec<(String, String)>,
+}
+
+#[derive(Facet)]
+struct Image {
+    repository: String,
+    tag: String,
+}
+
+fn main() {
+    let old = Config {
+        name: "web".into(),
+        replicas: 2,
+        image: Image {
+            repository: "nginx".into(),
+            tag: "1.25".into(),
+        },
+        ports: vec![80, 443],
+        labels: vec![
+            ("app".into(), "web".into()),
+            ("tier".into(), "<front>".into()),
+        ],
+    };
+
+    let new = Config {
+        name: "web".into(),
+        replicas: 3,
+        image: Image {
+            repository: "nginx".into(),
+            tag: "1.27".into(),
+        },
+        ports: vec![80, 443, 8080],
+        labels: vec![
+            ("app".into(), "web".into()),
+            ("tier".into(), "<front>".into()),
+        ],
+    };
+
+    print!("{}", old.diff(&new).html());
+}
@@ -0,0 +1,43 @@
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+#[derive(Facet)]
+struct Deployment {
+    name: String,
+    replicas: u32,
+    containers: Vec<Container>,
+}
+
+#[derive(Facet)]
+struct Container {
+    name: String,
+    image: String,
+}
+
+fn main() {
+    let old = Deployment {
+        name: "web".into(),
+        replicas: 2,
+        containers: vec![Container {
+            name: "nginx".into(),
+            image: "nginx:1.25".into(),
+        }],
+    };
+
+    let new = Deployment {
+        name: "web".into(),
+        replicas: 3,
+        containers: vec![
+            Container {
+                name: "nginx".into(),
+                image: "nginx:1.27".into(),
+            },
+            Container {
+                name: "sidecar".into(),
+                image: "envoy:1.30".into(),
+            },
+        ],
+    };
+
+    print!("{}", old.diff(&new).markdown());
+}
@@ -0,0 +1,31 @@
+use facet::Facet;
+use facet_diff::{FacetDiff, OwnedDiff};
+
+#[derive(Facet)]
+struct User {
+    name: String,
+    age: u8,
+    tags: Vec<String>,
+}
+
+fn main() {
+    let alice = User {
+        name: "Alice".to_string(),
+        age: 30,
+        tags: vec!["admin".to_string(), "ops".to_string()],
+    };
+
+    let bob = User {
+        name: "Bob".to_string(),
+        age: 30,
+        tags: vec!["ops".to_string(), "dev".to_string()],
+    };
+
+    let diff = OwnedDiff::from(&alice.diff(&bob));
+
+    let json = facet_json::to_string(&diff);
+    println!("{json}");
+
+    let back: OwnedDiff = facet_json::from_str(&json).unwrap();
+    assert_eq!(back, diff);
+}
@@ -0,0 +1,58 @@
+use facet::Facet;
+use facet_diff::FacetDiff;
+
+#[derive(Facet)]
+struct Response {
+    status: u16,
+    body: String,
+}
+
+#[derive(Facet)]
+struct ApiError {
+    code: u16,
+    message: String,
+}
+
+// `Result` does not implement `Facet` yet, any enum with only `Ok` and `Err` variants is treated
+// the same way.
+#[derive(Facet)]
+#[repr(C)]
+#[allow(dead_code)]
+enum ApiResult {
+    Ok(Response),
+    Err(ApiError),
+}
+
+fn main() {
+    let before = vec![
+        ApiResult::Ok(Response {
+            status: 200,
+            body: "hello".into(),
+        }),
+        ApiResult::Err(ApiError {
+            code: 404,
+            message: "not found".into(),
+        }),
+        ApiResult::Ok(Response {
+            status: 200,
+            body: "bye".into(),
+        }),
+    ];
+
+    let after = vec![
+        ApiResult::Ok(Response {
+            status: 200,
+            body: "hello, world".into(),
+        }),
+        ApiResult::Err(ApiError {
+            code: 410,
+            message: "gone".into(),
+        }),
+        ApiResult::Err(ApiError {
+            code: 500,
+            message: "internal error".into(),
+        }),
+    ];
+
+    println!("{}", before.diff(&after));
+}
@@ -0,0 +1,58 @@
+use facet::Facet;
+use facet_diff::similarity;
+
+#[derive(Facet)]
+struct Customer {
+    name: String,
+    email: String,
+    address: Address,
+}
+
+#[derive(Facet)]
+struct Address {
+    street: String,
+    city: String,
+    zip: String,
+}
+
+fn main() {
+    let record = Customer {
+        name: "Alice Smith".to_string(),
+        email: "alice@example.com".to_string(),
+        address: Address {
+            street: "123 Main St".to_string(),
+            city: "Wonderland".to_string(),
+            zip: "12345".to_string(),
+        },
+    };
+
+    let candidates = [
+        Customer {
+            name: "Alice Smith".to_string(),
+            email: "alice.smith@example.com".to_string(),
+            address: Address {
+                street: "1 Other Rd".to_string(),
+                city: "Elsewhere".to_string(),
+                zip: "99999".to_string(),
+            },
+        },
+        Customer {
+            name: "A. Smith".to_string(),
+            email: "asmith@example.com".to_string(),
+            address: Address {
+                street: "123 Main St".to_string(),
+                city: "Wonderland".to_string(),
+                zip: "12345".to_string(),
+            },
+        },
+    ];
+
+    for candidate in &candidates {
+        println!(
+            "{} <{}>: {:.2}",
+            candidate.name,
+            candidate.email,
+            similarity(&record, candidate)
+        );
+    }
+}
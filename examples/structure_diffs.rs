@@ -21,6 +21,9 @@ struct NewUser<'a> {
     address: Address,
 }
 
+#[derive(Facet)]
+struct Rgb(u8, u8, u8);
+
 #[derive(Facet)]
 struct Address {
     street: String,
@@ -63,4 +66,12 @@ fn main() {
     let diff = bob.diff(&alice);
 
     println!("{diff}");
+
+    let diff = Rgb(255, 128, 0).diff(&Rgb(255, 0, 128));
+
+    println!("{diff}");
+
+    let diff = (1, 2, 3).diff(&(1, 2));
+
+    println!("{diff}");
 }
@@ -0,0 +1,84 @@
+use facet::Facet;
+use facet_diff::{Diff, DiffVisitor, FacetDiff};
+use facet_reflect::Peek;
+
+#[derive(Facet)]
+struct Config {
+    name: String,
+    port: u16,
+    hosts: Vec<String>,
+    limits: Limits,
+}
+
+#[derive(Facet)]
+struct Limits {
+    connections: u32,
+    timeout: u32,
+}
+
+/// Prints every change along with the path of the field it happened in
+#[derive(Default)]
+struct PathPrinter {
+    path: Vec<&'static str>,
+}
+
+impl PathPrinter {
+    fn path(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for PathPrinter {
+    fn field_changed(&mut self, field: &'static str, diff: &Diff<'mem, 'facet>) {
+        self.path.push(field);
+        diff.walk(self);
+        self.path.pop();
+    }
+
+    fn field_added(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        println!("+ {}.{field}: {value}", self.path());
+    }
+
+    fn field_removed(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        println!("- {}.{field}: {value}", self.path());
+    }
+
+    fn element_added(&mut self, value: Peek<'mem, 'facet>) {
+        println!("+ {}[]: {value}", self.path());
+    }
+
+    fn element_removed(&mut self, value: Peek<'mem, 'facet>) {
+        println!("- {}[]: {value}", self.path());
+    }
+
+    fn leaf_replace(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {
+        println!("~ {}: {from} -> {to}", self.path());
+    }
+}
+
+fn main() {
+    let a = Config {
+        name: "production".to_string(),
+        port: 8080,
+        hosts: vec!["a.example.com".to_string(), "b.example.com".to_string()],
+        limits: Limits {
+            connections: 100,
+            timeout: 30,
+        },
+    };
+
+    let b = Config {
+        name: "production".to_string(),
+        port: 8443,
+        hosts: vec!["b.example.com".to_string(), "c.example.com".to_string()],
+        limits: Limits {
+            connections: 100,
+            timeout: 60,
+        },
+    };
+
+    let diff = a.diff(&b);
+    diff.walk(&mut PathPrinter::default());
+
+    println!("{}", diff.stats());
+}
@@ -0,0 +1,270 @@
+//! Compares two JSON, YAML or TOML files and prints their structural diff.
+
+use std::{io::IsTerminal, path::PathBuf, process::ExitCode};
+
+use facet_diff::Differ;
+
+mod patch;
+mod value;
+
+use patch::Patch;
+use value::Value;
+
+const USAGE: &str = "\
+Usage: facet-diff [OPTIONS] <OLD> <NEW>
+
+Compares two JSON, YAML or TOML files, detected by their extension, and prints
+their structural diff. Exits with 0 if they are equal, 1 if they differ and 2 on
+errors.
+
+Options:
+  -f, --format <FORMAT>   Output format: tree, unified, markdown or json-patch
+                          [default: tree]
+  -U, --context <LINES>   Unchanged values shown around each change in the
+                          unified format [default: 3]
+  -i, --ignore <PATH>     Ignore the values at a path, e.g. `metadata.labels`,
+                          `items[*].id` or `**.timestamp`; can be repeated
+  -I, --include <PATH>    Only compare the values at a path, e.g.
+                          `spec.containers[*].image`; can be repeated
+      --color <WHEN>      Color the output: auto, always or never [default: auto]
+  -h, --help              Print this help
+";
+
+#[derive(Clone, Copy)]
+enum Format {
+    Tree,
+    Unified,
+    Markdown,
+    JsonPatch,
+}
+
+struct Args {
+    old: PathBuf,
+    new: PathBuf,
+    format: Format,
+    context: usize,
+    ignore: Vec<String>,
+    include: Vec<String>,
+    color: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut files = Vec::new();
+        let mut format = Format::Tree;
+        let mut context = 3;
+        let mut ignore = Vec::new();
+        let mut include = Vec::new();
+        let mut color = None;
+
+        while let Some(arg) = args.next() {
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+                // Short options with their value attached, e.g. `-U0`
+                _ if !arg.starts_with("--") && arg.starts_with('-') && arg.len() > 2 => {
+                    (&arg[..2], Some(arg[2..].to_string()))
+                }
+                _ => (arg.as_str(), None),
+            };
+
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{name} expects a value"))
+            };
+
+            match name {
+                "-h" | "--help" => return Ok(None),
+                "-f" | "--format" => {
+                    format = match value()?.as_str() {
+                        "tree" => Format::Tree,
+                        "unified" => Format::Unified,
+                        "markdown" => Format::Markdown,
+                        "json-patch" => Format::JsonPatch,
+                        other => return Err(format!("unknown format `{other}`")),
+                    }
+                }
+                "-U" | "--context" => {
+                    let lines = value()?;
+                    context = lines
+                        .parse()
+                        .map_err(|_| format!("`{lines}` is not a number of lines"))?;
+                }
+                "-i" | "--ignore" => ignore.push(value()?),
+                "-I" | "--include" => include.push(value()?),
+                "--color" => {
+                    color = match value()?.as_str() {
+                        "auto" => None,
+                        "always" => Some(true),
+                        "never" => Some(false),
+                        other => return Err(format!("unknown color mode `{other}`")),
+                    }
+                }
+                _ if name.starts_with('-') && name.len() > 1 => {
+                    return Err(format!("unknown option `{name}`"));
+                }
+                _ => files.push(PathBuf::from(arg)),
+            }
+        }
+
+        let [old, new] = <[PathBuf; 2]>::try_from(files)
+            .map_err(|files| format!("expected 2 files, got {}", files.len()))?;
+
+        let color = color.unwrap_or_else(|| {
+            std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+        });
+
+        Ok(Some(Args {
+            old,
+            new,
+            format,
+            context,
+            ignore,
+            include,
+            color,
+        }))
+    }
+}
+
+/// Removes the ANSI escape codes from a rendering.
+fn strip_colors(rendered: &str) -> String {
+    let mut stripped = String::with_capacity(rendered.len());
+    let mut chars = rendered.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+/// Prints the diff, and returns whether the files differ.
+fn run(args: Args) -> Result<bool, String> {
+    let old = Value::load(&args.old)?;
+    let new = Value::load(&args.new)?;
+
+    let differ = args
+        .ignore
+        .iter()
+        .fold(Differ::new(), |differ, path| differ.ignore(path));
+    let differ = args
+        .include
+        .iter()
+        .fold(differ, |differ, path| differ.include(path));
+    let diff = differ.diff(&old, &new).map_err(|e| e.to_string())?;
+
+    match args.format {
+        Format::Tree if args.color => println!("{diff}"),
+        Format::Tree => println!("{}", strip_colors(&diff.to_string())),
+        Format::Unified => print!(
+            "{}",
+            diff.unified().context(args.context).colors(args.color)
+        ),
+        Format::Markdown => print!("{}", diff.markdown()),
+        Format::JsonPatch => println!("{}", Patch::new(&diff)),
+    }
+
+    Ok(!diff.is_equal())
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("facet-diff: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(false) => ExitCode::SUCCESS,
+        Ok(true) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("facet-diff: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn arguments_are_parsed() {
+        let args = parse(&["old.json", "new.yaml", "--color", "never"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.old, PathBuf::from("old.json"));
+        assert_eq!(args.new, PathBuf::from("new.yaml"));
+        assert!(matches!(args.format, Format::Tree));
+        assert_eq!(args.context, 3);
+        assert!(!args.color);
+
+        let args = parse(&[
+            "-f",
+            "unified",
+            "-U0",
+            "--ignore=metadata.labels",
+            "-i",
+            "**.timestamp",
+            "-Ispec",
+            "--color=always",
+            "a.toml",
+            "b.toml",
+        ])
+        .unwrap()
+        .unwrap();
+        assert!(matches!(args.format, Format::Unified));
+        assert_eq!(args.context, 0);
+        assert_eq!(args.ignore, ["metadata.labels", "**.timestamp"]);
+        assert_eq!(args.include, ["spec"]);
+        assert!(args.color);
+
+        let args = parse(&["--format", "json-patch", "a.json", "b.json"])
+            .unwrap()
+            .unwrap();
+        assert!(matches!(args.format, Format::JsonPatch));
+
+        assert!(parse(&["--help"]).unwrap().is_none());
+        assert!(parse(&["a.json", "-h"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported() {
+        let error = |args: &[&str]| parse(args).err().unwrap();
+
+        assert_eq!(error(&["a.json"]), "expected 2 files, got 1");
+        assert_eq!(
+            error(&["a.json", "b.json", "c.json"]),
+            "expected 2 files, got 3"
+        );
+        assert_eq!(error(&["-f", "xml", "a", "b"]), "unknown format `xml`");
+        assert_eq!(
+            error(&["-U", "many", "a", "b"]),
+            "`many` is not a number of lines"
+        );
+        assert_eq!(
+            error(&["--color=sometimes", "a", "b"]),
+            "unknown color mode `sometimes`"
+        );
+        assert_eq!(
+            error(&["--verbose", "a", "b"]),
+            "unknown option `--verbose`"
+        );
+        assert_eq!(error(&["a", "b", "--ignore"]), "--ignore expects a value");
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use facet_diff::{Diff, DiffVisitor};
+use facet_reflect::Peek;
+
+use crate::value::{Value, write_array, write_float, write_object, write_string};
+
+/// Collects the operations of a JSON Patch (RFC 6902) that turns the `from` document into the `to`
+/// document.
+///
+/// The operations are applied in order, so the indices of elements account for the elements
+/// removed and added before them.
+#[derive(Default)]
+pub struct Patch {
+    /// The JSON pointer to the current value, as escaped segments
+    path: Vec<String>,
+
+    /// The index of the next element in each sequence being patched
+    cursors: Vec<usize>,
+
+    operations: Vec<String>,
+}
+
+impl Patch {
+    pub fn new(diff: &Diff) -> Self {
+        let mut patch = Patch::default();
+        diff.walk(&mut patch);
+        patch
+    }
+
+    fn pointer(&self) -> String {
+        self.path
+            .iter()
+            .map(|segment| format!("/{segment}"))
+            .collect()
+    }
+
+    fn push(&mut self, op: &str, value: Option<String>) {
+        let mut operation = String::from("{\"op\":");
+        write_string(op, &mut operation);
+        operation.push_str(",\"path\":");
+        write_string(&self.pointer(), &mut operation);
+
+        if let Some(value) = value {
+            operation.push_str(",\"value\":");
+            operation.push_str(&value);
+        }
+
+        operation.push('}');
+        self.operations.push(operation);
+    }
+
+    fn entry(&mut self, key: &str, f: impl FnOnce(&mut Self)) {
+        self.path.push(key.replace('~', "~0").replace('/', "~1"));
+        f(self);
+        self.path.pop();
+    }
+
+    /// Visits the element at the cursor, and moves past it if it is still there afterwards.
+    fn element(&mut self, advance: bool, f: impl FnOnce(&mut Self)) {
+        let Some(cursor) = self.cursors.last_mut() else {
+            return f(self);
+        };
+
+        self.path.push(cursor.to_string());
+        *cursor += advance as usize;
+        f(self);
+        self.path.pop();
+    }
+}
+
+impl std::fmt::Display for Patch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.operations.is_empty() {
+            return f.write_str("[]");
+        }
+
+        writeln!(f, "[")?;
+        for (i, operation) in self.operations.iter().enumerate() {
+            let separator = if i + 1 < self.operations.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(f, "  {operation}{separator}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for Patch {
+    fn field_changed(&mut self, field: &'static str, diff: &Diff<'mem, 'facet>) {
+        self.entry(field, |patch| diff.walk(patch));
+    }
+
+    fn field_added(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        self.entry(field, |patch| patch.push("add", Some(json(value))));
+    }
+
+    fn field_removed(&mut self, field: &'static str, _value: Peek<'mem, 'facet>) {
+        self.entry(field, |patch| patch.push("remove", None));
+    }
+
+    fn entry_changed(&mut self, key: &str, diff: &Diff<'mem, 'facet>) {
+        self.entry(key, |patch| diff.walk(patch));
+    }
+
+    fn entry_added(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+        self.entry(key, |patch| patch.push("add", Some(json(value))));
+    }
+
+    fn entry_removed(&mut self, key: &str, _value: Peek<'mem, 'facet>) {
+        self.entry(key, |patch| patch.push("remove", None));
+    }
+
+    fn enter_sequence(&mut self, _from: &'static facet::Shape, _to: &'static facet::Shape) {
+        self.cursors.push(0);
+    }
+
+    fn exit_sequence(&mut self, _from: &'static facet::Shape, _to: &'static facet::Shape) {
+        self.cursors.pop();
+    }
+
+    fn element_kept(&mut self, _value: Peek<'mem, 'facet>) {
+        self.element(true, |_| {});
+    }
+
+    fn element_added(&mut self, value: Peek<'mem, 'facet>) {
+        self.element(true, |patch| patch.push("add", Some(json(value))));
+    }
+
+    fn element_removed(&mut self, _value: Peek<'mem, 'facet>) {
+        self.element(false, |patch| patch.push("remove", None));
+    }
+
+    fn element_replaced(&mut self, diff: &Diff<'mem, 'facet>) {
+        self.element(true, |patch| diff.walk(patch));
+    }
+
+    fn became_present(&mut self, value: Peek<'mem, 'facet>) {
+        self.push("replace", Some(json(value)));
+    }
+
+    fn became_absent(&mut self, _value: Peek<'mem, 'facet>) {
+        self.push("replace", Some("null".into()));
+    }
+
+    fn outcome_flipped(&mut self, _from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>, _ok: bool) {
+        self.push("replace", Some(json(to)));
+    }
+
+    fn leaf_replace(&mut self, _from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {
+        self.push("replace", Some(json(to)));
+    }
+}
+
+/// A value of the document as JSON, whether it is still wrapped in a [`Value`] or not.
+fn json(peek: Peek) -> String {
+    let mut out = String::new();
+    write_json(peek, &mut out);
+    out
+}
+
+fn write_json(peek: Peek, out: &mut String) {
+    if let Ok(value) = peek.get::<Value>() {
+        value.write_json(out);
+    } else if let Ok(boolean) = peek.get::<bool>() {
+        out.push_str(&boolean.to_string());
+    } else if let Ok(integer) = peek.get::<i64>() {
+        out.push_str(&integer.to_string());
+    } else if let Ok(float) = peek.get::<f64>() {
+        write_float(*float, out);
+    } else if let Ok(string) = peek.get::<String>() {
+        write_string(string, out);
+    } else if let Ok(array) = peek.get::<Vec<Value>>() {
+        write_array(array, out);
+    } else if let Ok(object) = peek.get::<BTreeMap<String, Value>>() {
+        write_object(object, out);
+    } else {
+        out.push_str("null");
+    }
+}
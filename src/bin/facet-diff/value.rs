@@ -0,0 +1,237 @@
+use std::{collections::BTreeMap, path::Path};
+
+use facet::Facet;
+
+// A document parsed from any of the supported formats.
+//
+// The enum is untagged, so that the diff goes straight to the values inside the variants, and
+// e.g. an integer compares equal to the same float.
+//
+// facet's format crates cannot build it: in 0.28, `facet-json` and `facet-yaml` do not deserialize
+// untagged enums, and `facet-toml` still targets `facet-core` 0.27. The files are parsed with
+// `serde_json`, `yaml-rust2` and `toml_edit` instead, and converted.
+#[derive(Facet)]
+#[repr(C)]
+#[facet(untagged)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+/// The formats that documents can be parsed from.
+#[derive(Clone, Copy)]
+enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    fn detect(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .unwrap_or_default();
+
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            _ => Err(format!(
+                "{}: unknown format, expected a .json, .yaml, .yml or .toml file",
+                path.display()
+            )),
+        }
+    }
+}
+
+impl Value {
+    /// Reads and parses a file, in the format given by its extension.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let format = Format::detect(path)?;
+        let source =
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        match format {
+            Format::Json => {
+                let json = serde_json::from_str::<serde_json::Value>(&source)
+                    .map_err(|e| format!("{}: {e}", path.display()))?;
+
+                Ok(Value::from_json(&json))
+            }
+            Format::Yaml => {
+                let documents = yaml_rust2::YamlLoader::load_from_str(&source)
+                    .map_err(|e| format!("{}: {e}", path.display()))?;
+
+                Ok(documents
+                    .first()
+                    .map(Value::from_yaml)
+                    .unwrap_or(Value::Null))
+            }
+            Format::Toml => {
+                let document = source
+                    .parse::<toml_edit::DocumentMut>()
+                    .map_err(|e| format!("{}: {e}", path.display()))?;
+
+                Ok(Value::from_table(document.as_table()))
+            }
+        }
+    }
+
+    fn from_json(json: &serde_json::Value) -> Self {
+        use serde_json::Value as Json;
+
+        match json {
+            Json::Null => Value::Null,
+            Json::Bool(boolean) => Value::Bool(*boolean),
+            Json::Number(number) => match number.as_i64() {
+                Some(integer) => Value::Integer(integer),
+                None => Value::Float(number.as_f64().unwrap_or(f64::NAN)),
+            },
+            Json::String(string) => Value::String(string.clone()),
+            Json::Array(array) => Value::Array(array.iter().map(Value::from_json).collect()),
+            Json::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Value::from_json(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn from_yaml(yaml: &yaml_rust2::Yaml) -> Self {
+        use yaml_rust2::Yaml;
+
+        match yaml {
+            Yaml::Real(real) => real
+                .parse()
+                .map(Value::Float)
+                .unwrap_or_else(|_| Value::String(real.clone())),
+            Yaml::Integer(integer) => Value::Integer(*integer),
+            Yaml::String(string) => Value::String(string.clone()),
+            Yaml::Boolean(boolean) => Value::Bool(*boolean),
+            Yaml::Array(array) => Value::Array(array.iter().map(Value::from_yaml).collect()),
+            Yaml::Hash(hash) => Value::Object(
+                hash.iter()
+                    .map(|(key, value)| {
+                        let key = match key {
+                            Yaml::String(key) | Yaml::Real(key) => key.clone(),
+                            Yaml::Integer(key) => key.to_string(),
+                            Yaml::Boolean(key) => key.to_string(),
+                            _ => "null".to_string(),
+                        };
+                        (key, Value::from_yaml(value))
+                    })
+                    .collect(),
+            ),
+            Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => Value::Null,
+        }
+    }
+
+    fn from_table(table: &toml_edit::Table) -> Self {
+        Value::Object(
+            table
+                .iter()
+                .map(|(key, item)| (key.to_string(), Value::from_item(item)))
+                .collect(),
+        )
+    }
+
+    fn from_item(item: &toml_edit::Item) -> Self {
+        use toml_edit::Item;
+
+        match item {
+            Item::None => Value::Null,
+            Item::Value(value) => Value::from_toml(value),
+            Item::Table(table) => Value::from_table(table),
+            Item::ArrayOfTables(tables) => {
+                Value::Array(tables.iter().map(Value::from_table).collect())
+            }
+        }
+    }
+
+    fn from_toml(value: &toml_edit::Value) -> Self {
+        use toml_edit::Value as Toml;
+
+        match value {
+            Toml::String(string) => Value::String(string.value().clone()),
+            Toml::Integer(integer) => Value::Integer(*integer.value()),
+            Toml::Float(float) => Value::Float(*float.value()),
+            Toml::Boolean(boolean) => Value::Bool(*boolean.value()),
+            Toml::Datetime(datetime) => Value::String(datetime.value().to_string()),
+            Toml::Array(array) => Value::Array(array.iter().map(Value::from_toml).collect()),
+            Toml::InlineTable(table) => Value::Object(
+                table
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), Value::from_toml(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Writes the value as JSON.
+    pub fn write_json(&self, out: &mut String) {
+        match self {
+            Value::Null => out.push_str("null"),
+            Value::Bool(boolean) => out.push_str(&boolean.to_string()),
+            Value::Integer(integer) => out.push_str(&integer.to_string()),
+            Value::Float(float) => write_float(*float, out),
+            Value::String(string) => write_string(string, out),
+            Value::Array(array) => write_array(array, out),
+            Value::Object(object) => write_object(object, out),
+        }
+    }
+}
+
+pub fn write_array(array: &[Value], out: &mut String) {
+    out.push('[');
+    for (i, value) in array.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        value.write_json(out);
+    }
+    out.push(']');
+}
+
+pub fn write_object(object: &BTreeMap<String, Value>, out: &mut String) {
+    out.push('{');
+    for (i, (key, value)) in object.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(key, out);
+        out.push(':');
+        value.write_json(out);
+    }
+    out.push('}');
+}
+
+pub fn write_float(float: f64, out: &mut String) {
+    if float.is_finite() {
+        out.push_str(&float.to_string());
+    } else {
+        out.push_str("null");
+    }
+}
+
+pub fn write_string(string: &str, out: &mut String) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
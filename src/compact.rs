@@ -0,0 +1,131 @@
+use std::fmt::{Display, Write};
+
+use crate::{
+    Diff,
+    elide::Limits,
+    unified::{self, Item, Kind},
+};
+
+/// A rendering of a [`Diff`] on a single line without colors, e.g. for logs.
+///
+/// Changes are separated by commas, e.g. `user.age: 30→31, user.tags[+2]: "x", address: <replaced>`.
+/// Added and removed elements are marked in their index, added and removed fields by a `+` or `-`
+/// before their path, and values that span several lines are left out. This is also what the
+/// alternate form of [`Diff`]'s `Display` (`{:#}`) writes.
+pub struct Compact<'a, 'mem, 'facet> {
+    diff: &'a Diff<'mem, 'facet>,
+    max_len: Option<usize>,
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Renders this diff on a single line, cf. [`Compact`].
+    pub fn compact(&self) -> Compact<'_, 'mem, 'facet> {
+        Compact {
+            diff: self,
+            max_len: None,
+        }
+    }
+}
+
+impl<'a, 'mem, 'facet> Compact<'a, 'mem, 'facet> {
+    /// Cuts the line to at most `max_len` characters, the last of which is then `…`.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+}
+
+/// Formats a value, or a placeholder if it spans several lines.
+fn value(item: &Item, placeholder: &str) -> String {
+    if item.text.contains('\n') {
+        placeholder.to_string()
+    } else if item.string {
+        format!("{:?}", item.text)
+    } else {
+        item.text.clone()
+    }
+}
+
+/// Writes an added or removed value, with the `sign` in its index if it is an element.
+fn write_one(out: &mut String, item: &Item, sign: char, placeholder: &str) {
+    let value = value(item, placeholder);
+
+    match item.index {
+        Some(index) => {
+            let parent = item
+                .path
+                .strip_suffix(&format!("[{index}]"))
+                .unwrap_or(&item.path);
+            let _ = write!(out, "{parent}[{sign}{index}]: {value}");
+        }
+        None if item.path.is_empty() => {
+            let _ = write!(out, "{sign}{value}");
+        }
+        None => {
+            let _ = write!(out, "{sign}{}: {value}", item.path);
+        }
+    }
+}
+
+impl<'a, 'mem, 'facet> Display for Compact<'a, 'mem, 'facet> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let items = unified::items(self.diff, Limits::default());
+        let mut items = items
+            .iter()
+            .filter(|item| item.kind != Kind::Unchanged)
+            .peekable();
+
+        let mut out = String::new();
+
+        while let Some(item) = items.next() {
+            if !out.is_empty() {
+                out.push_str(", ");
+            }
+
+            // A value replaced by another one at the same path
+            let replaced = item.kind == Kind::Removed
+                && items
+                    .peek()
+                    .is_some_and(|next| next.kind == Kind::Added && next.path == item.path);
+            let replaced = if replaced { items.next() } else { None };
+
+            if let Some(to) = replaced {
+                if !item.path.is_empty() {
+                    let _ = write!(out, "{}: ", item.path);
+                }
+
+                if item.text.contains('\n') || to.text.contains('\n') {
+                    out.push_str("<replaced>");
+                } else {
+                    let _ = write!(out, "{}→{}", value(item, ""), value(to, ""));
+                }
+                continue;
+            }
+
+            match item.kind {
+                Kind::Added => write_one(&mut out, item, '+', "<added>"),
+                Kind::Removed => write_one(&mut out, item, '-', "<removed>"),
+                _ if item.path.is_empty() => out.push_str(&item.text),
+                _ => {
+                    let _ = write!(out, "{}: {}", item.path, item.text);
+                }
+            }
+        }
+
+        if out.is_empty() {
+            out.push_str("equal");
+        }
+
+        match self.max_len {
+            Some(max_len) if out.chars().count() > max_len => {
+                let mut cut = out
+                    .chars()
+                    .take(max_len.saturating_sub(1))
+                    .collect::<String>();
+                cut.push('…');
+                f.write_str(&cut)
+            }
+            _ => f.write_str(&out),
+        }
+    }
+}
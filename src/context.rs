@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use facet::Shape;
+use facet_reflect::ValueId;
+
+use crate::{DiffError, options::DiffOptions, path::Segment};
+
+/// The state of a single diff, threaded through all the comparisons that it is made of.
+///
+/// This keeps track of the path to the values being compared, and of the values behind pointers
+/// that were compared, so that shared subtrees are only diffed once and cycles do not recurse forever.
+pub(crate) struct Context<'a> {
+    /// The configuration of the diff
+    pub(crate) options: &'a DiffOptions,
+
+    /// The path from the root values to the current comparison
+    path: Vec<Segment>,
+
+    /// The pairs of pointees that we followed to get to the current comparison
+    ancestors: HashSet<(ValueId, ValueId)>,
+
+    /// The pairs of pointees that were already compared, and whether they were equal
+    seen: HashMap<(ValueId, ValueId), bool>,
+
+    /// Whether the current comparisons are only used to compare candidates, and thrown away
+    scratch: bool,
+
+    /// The first error that happened, if any
+    pub(crate) error: Option<DiffError>,
+}
+
+/// What to do with a pair of pointees, cf. [`Context::visit`]
+pub(crate) enum Visit {
+    /// The pair was not compared yet
+    New,
+
+    /// The pair is being compared further up, i.e. the values are cyclic
+    Cycle,
+
+    /// The pair was already compared elsewhere, and was equal or not
+    Seen(bool),
+}
+
+impl<'a> Context<'a> {
+    pub(crate) fn new(options: &'a DiffOptions) -> Self {
+        Self {
+            options,
+            path: Vec::new(),
+            ancestors: HashSet::new(),
+            seen: HashMap::new(),
+            scratch: false,
+            error: None,
+        }
+    }
+
+    /// Runs a comparison of the children of the current values at `segment`.
+    pub(crate) fn nested<T>(&mut self, segment: Segment, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(segment);
+        let result = f(self);
+        self.path.pop();
+        result
+    }
+
+    /// Whether the current values are ignored, cf. [`Differ::ignore`](crate::Differ::ignore), or
+    /// outside of the included paths, cf. [`Differ::include`](crate::Differ::include).
+    pub(crate) fn is_ignored(&self) -> bool {
+        let included = self.options.include.is_empty()
+            || self
+                .options
+                .include
+                .iter()
+                .any(|pattern| pattern.contains(&self.path) || pattern.leads_to(&self.path));
+
+        !included
+            || self
+                .options
+                .ignore
+                .iter()
+                .any(|pattern| pattern.matches(&self.path))
+    }
+
+    /// Whether the current values are too deep to be walked, cf.
+    /// [`Differ::max_depth`](crate::Differ::max_depth).
+    pub(crate) fn is_too_deep(&self) -> bool {
+        self.options
+            .max_depth
+            .is_some_and(|depth| self.path.len() >= depth)
+    }
+
+    /// Checks whether a pair of pointees was compared already, and if not marks it as in progress
+    /// until [`Context::leave`] is called.
+    pub(crate) fn visit(&mut self, key: (ValueId, ValueId)) -> Visit {
+        if self.ancestors.contains(&key) {
+            Visit::Cycle
+        } else if let Some(equal) = self.seen.get(&key) {
+            Visit::Seen(*equal)
+        } else {
+            self.ancestors.insert(key);
+            Visit::New
+        }
+    }
+
+    /// Records that a pair of pointees was compared.
+    ///
+    /// The result is not reused when it depends on the path of the comparison, i.e. when some paths
+    /// are ignored or included, or the depth is limited. Shared pointees are then compared again at
+    /// every path, and only cycles are cut short.
+    pub(crate) fn leave(&mut self, key: (ValueId, ValueId), equal: bool) {
+        self.ancestors.remove(&key);
+
+        let depends_on_path = !self.options.ignore.is_empty()
+            || !self.options.include.is_empty()
+            || self.options.max_depth.is_some();
+
+        if !self.scratch && !depends_on_path {
+            self.seen.insert(key, equal);
+        }
+    }
+
+    /// Records that a value of this shape could not be accessed, cf.
+    /// [`BorrowPolicy::Error`](crate::BorrowPolicy::Error).
+    pub(crate) fn inaccessible(&mut self, shape: &'static Shape) {
+        self.error.get_or_insert(DiffError::Inaccessible(shape));
+    }
+
+    /// Runs comparisons that are thrown away afterwards, e.g. to find the closest candidate. These
+    /// do not count as having seen the values.
+    pub(crate) fn scratch<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let scratch = std::mem::replace(&mut self.scratch, true);
+        let result = f(self);
+        self.scratch = scratch;
+        result
+    }
+}
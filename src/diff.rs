@@ -1,10 +1,23 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
-use facet::{Def, Shape, StructKind, Type, UserType};
+use facet::{
+    Def, Field, FieldFlags, PointerType, Shape, ShapeAttribute, ShapeLayout, StructKind, Type,
+    UserType,
+};
 use facet_core::Facet;
-use facet_reflect::{HasFields, Peek};
-
-use crate::sequences::{self, Updates};
+use facet_reflect::{HasFields, Peek, ScalarType};
+
+use crate::{
+    BorrowPolicy, DiffError, Differ,
+    context::{Context, Visit},
+    interior,
+    maps::{self, Entries},
+    options::{DiffOptions, SequenceAlgorithm},
+    path::Segment,
+    positional::{self, Positional},
+    pretty,
+    sequences::{self, Updates},
+};
 
 /// The difference between two values.
 ///
@@ -32,8 +45,13 @@ pub enum Diff<'mem, 'facet> {
         /// The shape of the `to` struct.
         to: &'static Shape,
 
-        /// The name of the variant, this is [`None`] if the values are structs
-        variant: Option<&'static str>,
+        /// The name of the variant of the `from` enum, this is [`None`] if the values are structs
+        from_variant: Option<&'static str>,
+
+        /// The name of the variant of the `to` enum, this is [`None`] if the values are structs
+        ///
+        /// This can differ from `from_variant` if the two variants have fields in common.
+        to_variant: Option<&'static str>,
 
         /// cf. [Value]
         value: Value<'mem, 'facet>,
@@ -50,13 +68,110 @@ pub enum Diff<'mem, 'facet> {
         /// The updates on the sequence
         updates: Updates<'mem, 'facet>,
     },
+
+    /// A diff between two arrays or slices, or two lists with [`SequenceAlgorithm::Positional`],
+    /// compared index by index
+    Array {
+        /// The shape of the `from` array.
+        from: &'static Shape,
+
+        /// The shape of the `to` array.
+        to: &'static Shape,
+
+        /// The updates on the elements
+        updates: Positional<'mem, 'facet>,
+    },
+
+    /// A diff between two maps, or between a struct and a map, with entries matched by key
+    Map {
+        /// The shape of the `from` value.
+        from: &'static Shape,
+
+        /// The shape of the `to` value.
+        to: &'static Shape,
+
+        /// The updates on the entries
+        updates: Entries<'mem, 'facet>,
+    },
+
+    /// One of the values is `None` and the other is `Some`
+    Presence {
+        /// The shape of the `from` option.
+        from: &'static Shape,
+
+        /// The shape of the `to` option.
+        to: &'static Shape,
+
+        /// The value inside whichever option is `Some`
+        value: Peek<'mem, 'facet>,
+
+        /// Whether the value became present (`None` to `Some`), or absent (`Some` to `None`)
+        present: bool,
+    },
+
+    /// The values are behind pointers, and were already compared elsewhere in the diff
+    ///
+    /// This happens when a subtree is shared, e.g. through `Rc` or `Arc`, or when the values are
+    /// cyclic.
+    BackReference {
+        /// The `from` value.
+        from: Peek<'mem, 'facet>,
+
+        /// The `to` value.
+        to: Peek<'mem, 'facet>,
+
+        /// Whether the values are being compared further up, i.e. they contain themselves
+        cycle: bool,
+    },
+
+    /// One of the values is behind a lock or a cell that cannot be accessed, cf. [`BorrowPolicy`]
+    Inaccessible {
+        /// The `from` value.
+        from: Peek<'mem, 'facet>,
+
+        /// The `to` value.
+        to: Peek<'mem, 'facet>,
+    },
+
+    /// The values of a sensitive field (`#[facet(sensitive)]`), e.g. a password, differ
+    ///
+    /// The values themselves are left out so that they cannot end up in a rendering of the diff,
+    /// cf. [`Differ::reveal_sensitive`].
+    Redacted {
+        /// The shape of the `from` value.
+        from: &'static Shape,
+
+        /// The shape of the `to` value.
+        to: &'static Shape,
+    },
+
+    /// One of the values is `Ok` and the other is `Err`
+    ///
+    /// This applies to `Result` and to any enum whose only variants are `Ok` and `Err`, with a
+    /// single field each.
+    Outcome {
+        /// The shape of the `from` result.
+        from: &'static Shape,
+
+        /// The shape of the `to` result.
+        to: &'static Shape,
+
+        /// The value inside the `from` result
+        from_value: Peek<'mem, 'facet>,
+
+        /// The value inside the `to` result
+        to_value: Peek<'mem, 'facet>,
+
+        /// Whether the outcome went from `Err` to `Ok`, or from `Ok` to `Err`
+        ok: bool,
+    },
 }
 
 /// A set of updates, additions, deletions, insertions etc. for a tuple or a struct
 pub enum Value<'mem, 'facet> {
     Tuple {
-        /// The updates on the sequence
-        updates: Updates<'mem, 'facet>,
+        /// The updates on the fields, compared by position
+        updates: Positional<'mem, 'facet>,
     },
 
     Struct {
@@ -69,16 +184,150 @@ pub enum Value<'mem, 'facet> {
         /// The fields that are in `to` but not in `from`.
         insertions: HashMap<&'static str, Peek<'mem, 'facet>>,
 
-        /// The fields that are unchanged
-        unchanged: HashSet<&'static str>,
+        /// The fields that are unchanged, along with their value in `from`
+        unchanged: HashMap<&'static str, Peek<'mem, 'facet>>,
     },
 }
 
 impl<'mem, 'facet> Value<'mem, 'facet> {
-    fn closeness(&self) -> usize {
+    /// Diffs the fields of two structs or enum variants of the given kind.
+    ///
+    /// If `same_types` is set, fields are only compared when they have the same name and type,
+    /// otherwise they are compared whenever they have the same name.
+    fn new(
+        cx: &mut Context,
+        kind: StructKind,
+        from: Vec<(Field, Peek<'mem, 'facet>)>,
+        to: Vec<(Field, Peek<'mem, 'facet>)>,
+        same_types: bool,
+    ) -> Self {
+        let reveal = cx.options.reveal_sensitive;
+        let sensitive = |field: &Field| !reveal && field.flags.contains(FieldFlags::SENSITIVE);
+
+        if [StructKind::Tuple, StructKind::TupleStruct].contains(&kind) {
+            let values =
+                |fields: &[(Field, Peek<'mem, 'facet>)]| fields.iter().map(|x| x.1).collect();
+            let mut updates = positional::diff(cx, values(&from), values(&to));
+
+            // Only report that a sensitive field changed, rather than its values
+            let at = |fields: &[(Field, Peek<'mem, 'facet>)], i: usize| {
+                fields
+                    .get(i)
+                    .filter(|(field, _)| sensitive(field))
+                    .map(|x| x.1)
+            };
+
+            for (i, diff) in &mut updates.updates {
+                if at(&from, *i).is_some() || at(&to, *i).is_some() {
+                    *diff = Diff::redacted(from[*i].1, to[*i].1);
+                }
+            }
+
+            for (i, value) in std::mem::take(&mut updates.deletions) {
+                match at(&from, i) {
+                    Some(_) => updates.updates.push((i, Diff::redacted(value, value))),
+                    None => updates.deletions.push((i, value)),
+                }
+            }
+
+            for (i, value) in std::mem::take(&mut updates.insertions) {
+                match at(&to, i) {
+                    Some(_) => updates.updates.push((i, Diff::redacted(value, value))),
+                    None => updates.insertions.push((i, value)),
+                }
+            }
+
+            updates.updates.sort_by_key(|(i, _)| *i);
+
+            return Value::Tuple { updates };
+        }
+
+        let matches = |a: &Field, b: &Field| {
+            a.name == b.name && (!same_types || a.shape().id == b.shape().id)
+        };
+
+        let mut updates = HashMap::new();
+        let mut deletions = HashMap::new();
+        let mut insertions = HashMap::new();
+        let mut unchanged = HashMap::new();
+
+        let ignored = |cx: &mut Context, field: &Field| {
+            cx.nested(Segment::Field(field.name), |cx| cx.is_ignored())
+        };
+
+        for (field, from) in &from {
+            if let Some((to_field, to)) = to.iter().find(|(x, _)| matches(field, x)) {
+                let diff = cx.nested(Segment::Field(field.name), |cx| {
+                    Diff::new_peek(cx, *from, *to)
+                });
+                if diff.is_equal() {
+                    unchanged.insert(field.name, *from);
+                } else if sensitive(field) || sensitive(to_field) {
+                    updates.insert(field.name, Diff::redacted(*from, *to));
+                } else {
+                    updates.insert(field.name, diff);
+                }
+            } else if !ignored(cx, field) {
+                // Only report that a sensitive field changed, rather than the value it had
+                if sensitive(field) {
+                    updates.insert(field.name, Diff::redacted(*from, *from));
+                } else {
+                    deletions.insert(field.name, *from);
+                }
+            }
+        }
+
+        for (field, to) in &to {
+            if from.iter().any(|(x, _)| matches(x, field)) || ignored(cx, field) {
+                continue;
+            }
+
+            if sensitive(field) {
+                updates.insert(field.name, Diff::redacted(*to, *to));
+            } else {
+                insertions.insert(field.name, *to);
+            }
+        }
+
+        Value::Struct {
+            updates,
+            deletions,
+            insertions,
+            unchanged,
+        }
+    }
+
+    fn is_unchanged(&self) -> bool {
+        match self {
+            Self::Tuple { updates } => updates.is_empty(),
+            Self::Struct {
+                updates,
+                deletions,
+                insertions,
+                ..
+            } => updates.is_empty() && deletions.is_empty() && insertions.is_empty(),
+        }
+    }
+
+    fn invert(self) -> Self {
         match self {
-            Self::Tuple { updates } => updates.closeness(),
-            Self::Struct { unchanged, .. } => unchanged.len(),
+            Self::Tuple { updates } => Self::Tuple {
+                updates: updates.invert(),
+            },
+            Self::Struct {
+                updates,
+                deletions,
+                insertions,
+                unchanged,
+            } => Self::Struct {
+                updates: updates
+                    .into_iter()
+                    .map(|(field, diff)| (field, diff.invert()))
+                    .collect(),
+                deletions: insertions,
+                insertions: deletions,
+                unchanged,
+            },
         }
     }
 }
@@ -101,71 +350,288 @@ impl<'mem, 'facet> Diff<'mem, 'facet> {
         matches!(self, Self::Equal)
     }
 
+    /// Inverts the diff, so that it goes from the `to` value to the `from` value.
+    ///
+    /// Every insertion becomes a deletion and vice versa, and the `from` and `to` sides are swapped
+    /// everywhere. Applying the inverted diff to the `to` value yields the `from` value, which makes
+    /// this useful for undo without having to keep both values around to compute the reverse diff.
+    pub fn invert(self) -> Self {
+        match self {
+            Self::Equal => Self::Equal,
+            Self::Replace { from, to } => Self::Replace { from: to, to: from },
+            Self::User {
+                from,
+                to,
+                from_variant,
+                to_variant,
+                value,
+            } => Self::User {
+                from: to,
+                to: from,
+                from_variant: to_variant,
+                to_variant: from_variant,
+                value: value.invert(),
+            },
+            Self::Sequence { from, to, updates } => Self::Sequence {
+                from: to,
+                to: from,
+                updates: updates.invert(),
+            },
+            Self::Array { from, to, updates } => Self::Array {
+                from: to,
+                to: from,
+                updates: updates.invert(),
+            },
+            Self::Map { from, to, updates } => Self::Map {
+                from: to,
+                to: from,
+                updates: updates.invert(),
+            },
+            Self::Presence {
+                from,
+                to,
+                value,
+                present,
+            } => Self::Presence {
+                from: to,
+                to: from,
+                value,
+                present: !present,
+            },
+            Self::BackReference { from, to, cycle } => Self::BackReference {
+                from: to,
+                to: from,
+                cycle,
+            },
+            Self::Inaccessible { from, to } => Self::Inaccessible { from: to, to: from },
+            Self::Redacted { from, to } => Self::Redacted { from: to, to: from },
+            Self::Outcome {
+                from,
+                to,
+                from_value,
+                to_value,
+                ok,
+            } => Self::Outcome {
+                from: to,
+                to: from,
+                from_value: to_value,
+                to_value: from_value,
+                ok: !ok,
+            },
+        }
+    }
+
+    /// A diff between the values of a sensitive field, which does not keep the values.
+    pub(crate) fn redacted(from: Peek, to: Peek) -> Self {
+        Self::Redacted {
+            from: from.shape(),
+            to: to.shape(),
+        }
+    }
+
     /// Computes the difference between two values that implement `Facet`
+    ///
+    /// Use a [`Differ`] to configure how the values are compared.
     pub fn new<T: Facet<'facet>, U: Facet<'facet>>(from: &'mem T, to: &'mem U) -> Self {
-        Self::new_peek(Peek::new(from), Peek::new(to))
+        let options = DiffOptions::default();
+        Self::new_peek(&mut Context::new(&options), Peek::new(from), Peek::new(to))
     }
 
-    pub(crate) fn new_peek(from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) -> Self {
+    /// Computes the difference between two values, with the given policy for values behind locks or
+    /// cells that cannot be accessed.
+    ///
+    /// This only fails with [`BorrowPolicy::Error`]. This is a shorthand for
+    /// [`Differ::borrow_policy`].
+    pub fn with_policy<T: Facet<'facet>, U: Facet<'facet>>(
+        from: &'mem T,
+        to: &'mem U,
+        policy: BorrowPolicy,
+    ) -> Result<Self, DiffError> {
+        Differ::new().borrow_policy(policy).diff(from, to)
+    }
+
+    pub(crate) fn new_peek(
+        cx: &mut Context,
+        from: Peek<'mem, 'facet>,
+        to: Peek<'mem, 'facet>,
+    ) -> Self {
+        if cx.is_ignored() {
+            return Diff::Equal;
+        }
+
+        if let Some(diff) = cx.options.registry.diff(from, to) {
+            return diff;
+        }
+
         if from.shape().id == to.shape().id && from.shape().is_partial_eq() && from == to {
             return Diff::Equal;
         }
 
-        match (
+        // References to strings cannot be compared through their shape
+        if let (Some(from_str), Some(to_str)) = (as_str(from), as_str(to)) {
+            if from_str == to_str {
+                return Diff::Equal;
+            }
+        }
+
+        // Numbers of different types, e.g. from a typed struct and a loosely typed document, or
+        // numbers within the tolerance
+        if from.shape().id != to.shape().id || cx.options.tolerance > 0.0 {
+            if let (Some(from_number), Some(to_number)) = (as_number(from), as_number(to)) {
+                if from_number.distance(to_number) <= cx.options.tolerance {
+                    return Diff::Equal;
+                }
+            }
+        }
+
+        // Untagged enums, e.g. dynamic values, are compared through the value of their variant
+        if let Some(from) = untagged(from) {
+            return Self::new_peek(cx, from, to);
+        }
+        if let Some(to) = untagged(to) {
+            return Self::new_peek(cx, from, to);
+        }
+
+        if pointee(from).is_some() || pointee(to).is_some() {
+            let from = pointee(from).unwrap_or(from);
+            let to = pointee(to).unwrap_or(to);
+            let key = (from.id(), to.id());
+
+            return match cx.visit(key) {
+                Visit::Cycle => Diff::BackReference {
+                    from,
+                    to,
+                    cycle: true,
+                },
+                Visit::Seen(true) => Diff::Equal,
+                Visit::Seen(false) => Diff::BackReference {
+                    from,
+                    to,
+                    cycle: false,
+                },
+                Visit::New => {
+                    let diff = Self::new_peek(cx, from, to);
+                    cx.leave(key, diff.is_equal());
+                    diff
+                }
+            };
+        }
+
+        for shape in [from.shape(), to.shape()] {
+            if interior::is_interior(shape) {
+                return match cx.options.policy {
+                    BorrowPolicy::Skip => Diff::Equal,
+                    BorrowPolicy::Report => Diff::Inaccessible { from, to },
+                    BorrowPolicy::Error => {
+                        cx.inaccessible(shape);
+                        Diff::Inaccessible { from, to }
+                    }
+                };
+            }
+        }
+
+        // Values that can be compared with `PartialEq` and are equal were caught above
+        if cx.is_too_deep() {
+            let comparable = from.shape().id == to.shape().id && from.shape().is_partial_eq();
+
+            return match !comparable && pretty::same_text(from, to) {
+                true => Diff::Equal,
+                false => Diff::Replace { from, to },
+            };
+        }
+
+        let diff = match (
             (from.shape().def, from.shape().ty),
             (to.shape().def, to.shape().ty),
         ) {
+            // This goes before the enum case, since some options are also represented as enums
+            ((Def::Option(_), _), (Def::Option(_), _)) => {
+                let from_option = from.into_option().unwrap();
+                let to_option = to.into_option().unwrap();
+
+                match (from_option.value(), to_option.value()) {
+                    (Some(from_value), Some(to_value)) => Diff::User {
+                        from: from.shape(),
+                        to: to.shape(),
+                        from_variant: Some("Some"),
+                        to_variant: Some("Some"),
+                        value: Value::Tuple {
+                            updates: positional::single(cx, from_value, to_value),
+                        },
+                    },
+                    (None, Some(value)) => Diff::Presence {
+                        from: from.shape(),
+                        to: to.shape(),
+                        value,
+                        present: true,
+                    },
+                    (Some(value), None) => Diff::Presence {
+                        from: from.shape(),
+                        to: to.shape(),
+                        value,
+                        present: false,
+                    },
+                    // Two `None`s of different types
+                    (None, None) => Diff::Replace { from, to },
+                }
+            }
+            _ if outcome(from).is_some() && outcome(to).is_some() => {
+                let (from_ok, from_value) = outcome(from).unwrap();
+                let (to_ok, to_value) = outcome(to).unwrap();
+
+                if from_ok != to_ok {
+                    return Diff::Outcome {
+                        from: from.shape(),
+                        to: to.shape(),
+                        from_value,
+                        to_value,
+                        ok: to_ok,
+                    };
+                }
+
+                let variant = if from_ok { "Ok" } else { "Err" };
+
+                Diff::User {
+                    from: from.shape(),
+                    to: to.shape(),
+                    from_variant: Some(variant),
+                    to_variant: Some(variant),
+                    value: Value::Tuple {
+                        updates: positional::single(cx, from_value, to_value),
+                    },
+                }
+            }
+            // Maps are compared entry by entry, also against structs
+            ((Def::Map(_), _), _) | (_, (Def::Map(_), _)) => {
+                match (maps::entries(from), maps::entries(to)) {
+                    (Some(from_entries), Some(to_entries)) => Diff::Map {
+                        from: from.shape(),
+                        to: to.shape(),
+                        updates: maps::diff(cx, from_entries, to_entries),
+                    },
+                    _ => Diff::Replace { from, to },
+                }
+            }
             (
                 (_, Type::User(UserType::Struct(from_ty))),
                 (_, Type::User(UserType::Struct(to_ty))),
             ) if from_ty.kind == to_ty.kind => {
-                let from_ty = from.into_struct().unwrap();
-                let to_ty = to.into_struct().unwrap();
-
-                let value =
-                    if [StructKind::Tuple, StructKind::TupleStruct].contains(&from_ty.ty().kind) {
-                        let from = from_ty.fields().map(|x| x.1).collect();
-                        let to = to_ty.fields().map(|x| x.1).collect();
-
-                        let updates = sequences::diff(from, to);
-
-                        Value::Tuple { updates }
-                    } else {
-                        let mut updates = HashMap::new();
-                        let mut deletions = HashMap::new();
-                        let mut insertions = HashMap::new();
-                        let mut unchanged = HashSet::new();
-
-                        for (field, from) in from_ty.fields() {
-                            if let Ok(to) = to_ty.field_by_name(field.name) {
-                                let diff = Diff::new_peek(from, to);
-                                if diff.is_equal() {
-                                    unchanged.insert(field.name);
-                                } else {
-                                    updates.insert(field.name, diff);
-                                }
-                            } else {
-                                deletions.insert(field.name, from);
-                            }
-                        }
-
-                        for (field, to) in to_ty.fields() {
-                            if from_ty.field_by_name(field.name).is_err() {
-                                insertions.insert(field.name, to);
-                            }
-                        }
-                        Value::Struct {
-                            updates,
-                            deletions,
-                            insertions,
-                            unchanged,
-                        }
-                    };
+                let from_struct = from.into_struct().unwrap();
+                let to_struct = to.into_struct().unwrap();
+
+                let value = Value::new(
+                    cx,
+                    from_ty.kind,
+                    from_struct.fields().collect(),
+                    to_struct.fields().collect(),
+                    false,
+                );
 
                 Diff::User {
                     from: from.shape(),
                     to: to.shape(),
-                    variant: None,
+                    from_variant: None,
+                    to_variant: None,
                     value,
                 }
             }
@@ -176,96 +642,67 @@ impl<'mem, 'facet> Diff<'mem, 'facet> {
                 let from_variant = from_enum.active_variant().unwrap();
                 let to_variant = to_enum.active_variant().unwrap();
 
-                if from_variant.name != to_variant.name
-                    || from_variant.data.kind != to_variant.data.kind
-                {
+                if from_variant.data.kind != to_variant.data.kind {
                     return Diff::Replace { from, to };
                 }
 
-                let value = if [StructKind::Tuple, StructKind::TupleStruct]
-                    .contains(&from_variant.data.kind)
+                let from_fields = from_enum.fields().collect::<Vec<_>>();
+                let to_fields = to_enum.fields().collect::<Vec<_>>();
+
+                // When the variant changes, we only keep diffing if the variants have a field in common
+                let variant_changed = from_variant.name != to_variant.name;
+                if variant_changed
+                    && !from_fields.iter().any(|(from, _)| {
+                        to_fields
+                            .iter()
+                            .any(|(to, _)| from.name == to.name && from.shape().id == to.shape().id)
+                    })
                 {
-                    let from = from_enum.fields().map(|x| x.1).collect();
-                    let to = to_enum.fields().map(|x| x.1).collect();
-
-                    let updates = sequences::diff(from, to);
-
-                    Value::Tuple { updates }
-                } else {
-                    let mut updates = HashMap::new();
-                    let mut deletions = HashMap::new();
-                    let mut insertions = HashMap::new();
-                    let mut unchanged = HashSet::new();
-
-                    for (field, from) in from_enum.fields() {
-                        if let Ok(Some(to)) = to_enum.field_by_name(field.name) {
-                            let diff = Diff::new_peek(from, to);
-                            if diff.is_equal() {
-                                unchanged.insert(field.name);
-                            } else {
-                                updates.insert(field.name, diff);
-                            }
-                        } else {
-                            deletions.insert(field.name, from);
-                        }
-                    }
-
-                    for (field, to) in to_enum.fields() {
-                        if !from_enum
-                            .field_by_name(field.name)
-                            .is_ok_and(|x| x.is_some())
-                        {
-                            insertions.insert(field.name, to);
-                        }
-                    }
+                    return Diff::Replace { from, to };
+                }
 
-                    Value::Struct {
-                        updates,
-                        deletions,
-                        insertions,
-                        unchanged,
-                    }
-                };
+                let value = Value::new(
+                    cx,
+                    from_variant.data.kind,
+                    from_fields,
+                    to_fields,
+                    variant_changed,
+                );
 
                 Diff::User {
                     from: from_enum.shape(),
                     to: to_enum.shape(),
-                    variant: Some(from_variant.name),
+                    from_variant: Some(from_variant.name),
+                    to_variant: Some(to_variant.name),
                     value,
                 }
             }
-            ((Def::Option(_), _), (Def::Option(_), _)) => {
-                let from_option = from.into_option().unwrap();
-                let to_option = to.into_option().unwrap();
-
-                let (Some(from_value), Some(to_value)) = (from_option.value(), to_option.value())
-                else {
-                    return Diff::Replace { from, to };
-                };
-
-                let mut updates = Updates::default();
+            _ if is_array(from.shape()) && is_array(to.shape())
+                || cx.options.sequences == SequenceAlgorithm::Positional
+                    && is_list(from.shape())
+                    && is_list(to.shape()) =>
+            {
+                let from_list = from.into_list_like().unwrap();
+                let to_list = to.into_list_like().unwrap();
 
-                let diff = Self::new_peek(from_value, to_value);
-                if !diff.is_equal() {
-                    updates.push_add(to_value);
-                    updates.push_remove(from_value);
-                }
+                let updates = positional::diff(
+                    cx,
+                    from_list.iter().collect::<Vec<_>>(),
+                    to_list.iter().collect::<Vec<_>>(),
+                );
 
-                Diff::User {
+                Diff::Array {
                     from: from.shape(),
                     to: to.shape(),
-                    variant: Some("Some"),
-                    value: Value::Tuple { updates },
+                    updates,
                 }
             }
-            (
-                (Def::List(_), _) | (_, Type::Sequence(_)),
-                (Def::List(_), _) | (_, Type::Sequence(_)),
-            ) => {
+            _ if is_list(from.shape()) && is_list(to.shape()) => {
                 let from_list = from.into_list_like().unwrap();
                 let to_list = to.into_list_like().unwrap();
 
                 let updates = sequences::diff(
+                    cx,
                     from_list.iter().collect::<Vec<_>>(),
                     to_list.iter().collect::<Vec<_>>(),
                 );
@@ -277,17 +714,207 @@ impl<'mem, 'facet> Diff<'mem, 'facet> {
                 }
             }
             _ => Diff::Replace { from, to },
+        };
+
+        // Values without a comparison function can still turn out to be equal
+        if from.shape().id == to.shape().id && diff.is_unchanged() {
+            return Diff::Equal;
         }
+
+        diff
     }
 
-    pub(crate) fn closeness(&self) -> usize {
+    /// Whether the diff is structural, but does not contain any change.
+    fn is_unchanged(&self) -> bool {
         match self {
-            Self::Equal => 1, // This does not actually matter for flattening sequence diffs, because all diffs there are non-equal
-            Self::Replace { .. } => 0,
-            Self::Sequence { updates, .. } => updates.closeness(),
             Self::User {
-                from, to, value, ..
-            } => value.closeness() + (from == to) as usize,
+                from_variant,
+                to_variant,
+                value,
+                ..
+            } => from_variant == to_variant && value.is_unchanged(),
+            Self::Sequence { updates, .. } => updates.is_unchanged(),
+            Self::Array { updates, .. } => updates.is_empty(),
+            Self::Map { updates, .. } => {
+                updates.updates.is_empty()
+                    && updates.deletions.is_empty()
+                    && updates.insertions.is_empty()
+            }
+            _ => false,
         }
     }
+
+    /// A leaf-weighted measure of how much the two values have in common, used to pair up removals
+    /// and additions in sequences. Values of the same type get a small bonus.
+    pub(crate) fn closeness(&self) -> usize {
+        let bonus = match self {
+            Self::User { from, to, .. }
+            | Self::Map { from, to, .. }
+            | Self::Presence { from, to, .. }
+            | Self::Outcome { from, to, .. } => (from == to) as usize,
+            _ => 0,
+        };
+
+        self.weights().common + bonus
+    }
+}
+
+/// If the value is a `Result`, or an enum that looks like one, returns whether it is `Ok` along with
+/// the value inside it.
+fn outcome<'mem, 'facet>(peek: Peek<'mem, 'facet>) -> Option<(bool, Peek<'mem, 'facet>)> {
+    if !is_outcome(peek.shape()) {
+        return None;
+    }
+
+    let peek = peek.into_enum().ok()?;
+    let ok = peek.variant_name_active().ok()? == "Ok";
+    Some((ok, peek.field(0).ok()??))
+}
+
+/// Whether the shape is a `Result`, or an enum whose only variants are `Ok` and `Err` with a single
+/// field each.
+fn is_outcome(shape: &'static Shape) -> bool {
+    let Type::User(UserType::Enum(ty)) = shape.ty else {
+        return false;
+    };
+
+    let has_variant = |name| {
+        ty.variants
+            .iter()
+            .any(|variant| variant.name == name && variant.data.fields.len() == 1)
+    };
+    ty.variants.len() == 2 && has_variant("Ok") && has_variant("Err")
+}
+
+/// Whether the shape wraps a single value that does not appear in paths, i.e. it is an option or an
+/// outcome, whose `Some`, `Ok` or `Err` values are compared directly.
+pub(crate) fn is_wrapper(shape: &'static Shape) -> bool {
+    matches!(shape.def, Def::Option(_)) || is_outcome(shape)
+}
+
+/// Whether the shape is an array, a slice or a reference to a slice, which are diffed index by index
+/// rather than with the sequence algorithm.
+fn is_array(shape: &'static Shape) -> bool {
+    match (shape.def, shape.ty) {
+        (Def::Array(_) | Def::Slice(_), _) => true,
+        (_, Type::Pointer(PointerType::Reference(pointer))) => {
+            matches!((pointer.target)().def, Def::Slice(_))
+        }
+        _ => false,
+    }
+}
+
+/// Whether the shape is a list or another sequence, which is diffed with the sequence algorithm.
+fn is_list(shape: &'static Shape) -> bool {
+    matches!(
+        (shape.def, shape.ty),
+        (Def::List(_), _) | (_, Type::Sequence(_))
+    )
+}
+
+/// If the value is a pointer that can be followed, e.g. a `Box`, an `Rc` or a reference, returns the
+/// value it points to.
+fn pointee<'mem, 'facet>(peek: Peek<'mem, 'facet>) -> Option<Peek<'mem, 'facet>> {
+    let Def::Pointer(def) = peek.shape().def else {
+        return None;
+    };
+
+    // Locks are left to the borrow policy, cf. `BorrowPolicy`
+    if interior::is_lock(peek.shape()) {
+        return None;
+    }
+
+    // The pointers to unsized values, e.g. slices, are handled by their own shape
+    if def.vtable.borrow_fn.is_none() || !matches!(def.pointee()?.layout, ShapeLayout::Sized(_)) {
+        return None;
+    }
+
+    peek.into_pointer().ok()?.borrow_inner()
+}
+
+/// Returns the value as a string if it is a `String` or a `&str`.
+pub(crate) fn as_str<'mem>(peek: Peek<'mem, '_>) -> Option<&'mem str> {
+    // `Peek::as_str` unwraps wrapper types first, which fails on e.g. a `None` option
+    if peek.shape().inner.is_some() {
+        return None;
+    }
+
+    peek.as_str()
+}
+
+/// A number of any primitive type, with integers kept exact.
+#[derive(Clone, Copy)]
+enum Number {
+    Signed(i128),
+    Unsigned(u128),
+    Float(f64),
+}
+
+impl Number {
+    /// The absolute difference between two numbers, which is only approximated by a float when one
+    /// of them is a float, or when it is too large to be exact anyway.
+    fn distance(self, other: Number) -> f64 {
+        match (self, other) {
+            (Number::Signed(a), Number::Signed(b)) => a.abs_diff(b) as f64,
+            (Number::Unsigned(a), Number::Unsigned(b)) => a.abs_diff(b) as f64,
+            (Number::Signed(a), Number::Unsigned(b)) | (Number::Unsigned(b), Number::Signed(a)) => {
+                match u128::try_from(a) {
+                    Ok(a) => a.abs_diff(b) as f64,
+                    Err(_) => b.saturating_add(a.unsigned_abs()) as f64,
+                }
+            }
+            (a, b) => {
+                let (a, b) = (a.as_f64(), b.as_f64());
+                if a == b { 0.0 } else { (a - b).abs() }
+            }
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Signed(n) => n as f64,
+            Number::Unsigned(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+}
+
+/// Returns the value as a number if it is one, to compare numbers of different types.
+fn as_number(peek: Peek) -> Option<Number> {
+    Some(match ScalarType::try_from_shape(peek.shape())? {
+        ScalarType::F32 => Number::Float(*peek.get::<f32>().ok()? as f64),
+        ScalarType::F64 => Number::Float(*peek.get::<f64>().ok()?),
+        ScalarType::U8 => Number::Unsigned(*peek.get::<u8>().ok()? as u128),
+        ScalarType::U16 => Number::Unsigned(*peek.get::<u16>().ok()? as u128),
+        ScalarType::U32 => Number::Unsigned(*peek.get::<u32>().ok()? as u128),
+        ScalarType::U64 => Number::Unsigned(*peek.get::<u64>().ok()? as u128),
+        ScalarType::U128 => Number::Unsigned(*peek.get::<u128>().ok()?),
+        ScalarType::USize => Number::Unsigned(*peek.get::<usize>().ok()? as u128),
+        ScalarType::I8 => Number::Signed(*peek.get::<i8>().ok()? as i128),
+        ScalarType::I16 => Number::Signed(*peek.get::<i16>().ok()? as i128),
+        ScalarType::I32 => Number::Signed(*peek.get::<i32>().ok()? as i128),
+        ScalarType::I64 => Number::Signed(*peek.get::<i64>().ok()? as i128),
+        ScalarType::I128 => Number::Signed(*peek.get::<i128>().ok()?),
+        ScalarType::ISize => Number::Signed(*peek.get::<isize>().ok()? as i128),
+        _ => return None,
+    })
+}
+
+/// If the value is an untagged enum (`#[facet(untagged)]`) with a single field in its variant,
+/// returns the value of that field.
+pub(crate) fn untagged<'mem, 'facet>(peek: Peek<'mem, 'facet>) -> Option<Peek<'mem, 'facet>> {
+    if !peek
+        .shape()
+        .attributes
+        .contains(&ShapeAttribute::Arbitrary("untagged"))
+    {
+        return None;
+    }
+
+    let peek = peek.into_enum().ok()?;
+    if peek.active_variant().ok()?.data.fields.len() != 1 {
+        return None;
+    }
+
+    peek.field(0).ok()?
 }
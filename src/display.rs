@@ -1,11 +1,13 @@
 use std::fmt::{Display, Write};
 
 use facet::TypeNameOpts;
-use facet_pretty::PrettyPrinter;
 
 use crate::{
-    diff::{Diff, Value},
-    sequences::{ReplaceGroup, Updates, UpdatesGroup},
+    diff::{self, Diff, Value},
+    elide::Limits,
+    hexdump,
+    positional::{Common, Positional},
+    sequences::{ElementChange, Updates},
 };
 
 struct PadAdapter<'a, 'b: 'a> {
@@ -38,13 +40,72 @@ impl<'a, 'b> Write for PadAdapter<'a, 'b> {
     }
 }
 
+/// A rendering of a [`Diff`] as a tree, which is what `Display` writes, with limits on the size of
+/// the values.
+///
+/// Values that exceed the limits are elided in the middle, with a marker saying how many characters
+/// or lines were left out, e.g. `[… 120 lines …]`. Strings that replace each other are cut around
+/// the region where they differ.
+pub struct Tree<'a, 'mem, 'facet> {
+    diff: &'a Diff<'mem, 'facet>,
+    limits: Limits,
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Renders this diff as a tree, cf. [`Tree`].
+    pub fn tree(&self) -> Tree<'_, 'mem, 'facet> {
+        Tree {
+            diff: self,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl<'a, 'mem, 'facet> Tree<'a, 'mem, 'facet> {
+    /// Elides the values, or the lines of values, that are longer than `max_len` characters.
+    pub fn max_value_len(mut self, max_len: usize) -> Self {
+        self.limits.max_len = Some(max_len);
+        self
+    }
+
+    /// Elides the values that span more than `max_lines` lines.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.limits.max_lines = Some(max_lines);
+        self
+    }
+
+    /// Wraps the lines of values at `width` characters, e.g. the width of the terminal.
+    pub fn width(mut self, width: usize) -> Self {
+        self.limits.width = Some(width);
+        self
+    }
+
+    fn nested<'b>(&self, diff: &'b Diff<'mem, 'facet>) -> Tree<'b, 'mem, 'facet> {
+        Tree {
+            diff,
+            limits: self.limits,
+        }
+    }
+}
+
 impl<'mem, 'facet> Display for Diff<'mem, 'facet> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
+        // `{:#}` writes the diff on a single line
+        if f.alternate() {
+            return write!(f, "{}", self.compact());
+        }
+
+        self.tree().fmt(f)
+    }
+}
+
+impl<'a, 'mem, 'facet> Display for Tree<'a, 'mem, 'facet> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let limits = &self.limits;
+
+        match self.diff {
             Diff::Equal => f.write_str("equal"),
             Diff::Replace { from, to } => {
-                let printer = PrettyPrinter::default().with_colors(false);
-
                 if from.shape().id != to.shape().id {
                     f.write_str("\x1b[1m")?;
                     from.type_name(f, TypeNameOpts::infinite())?;
@@ -60,17 +121,18 @@ impl<'mem, 'facet> Display for Diff<'mem, 'facet> {
                     on_newline: true,
                 };
 
-                writeln!(indent, "{}\x1b[32m", printer.format_peek(*from))?;
-                write!(indent, "{}", printer.format_peek(*to))?;
+                let (from, to) = limits.format_pair(*from, *to);
+                writeln!(indent, "{from}\x1b[32m")?;
+                write!(indent, "{to}")?;
                 f.write_str("\n\x1b[m}") // Reset the colors
             }
             Diff::User {
                 from,
                 to,
-                variant,
+                from_variant,
+                to_variant,
                 value,
             } => {
-                let printer = PrettyPrinter::default().with_colors(false);
                 let mut indent = PadAdapter {
                     fmt: f,
                     on_newline: false,
@@ -79,15 +141,15 @@ impl<'mem, 'facet> Display for Diff<'mem, 'facet> {
                 write!(indent, "\x1b[1m")?;
                 from.write_type_name(indent.fmt, TypeNameOpts::infinite())?;
 
-                if let Some(variant) = variant {
+                if let Some(variant) = from_variant {
                     write!(indent, "\x1b[m::\x1b[1m{variant}")?;
                 }
 
-                if from.id != to.id {
+                if from.id != to.id || from_variant != to_variant {
                     write!(indent, "\x1b[m => \x1b[1m")?;
                     to.write_type_name(indent.fmt, TypeNameOpts::infinite())?;
 
-                    if let Some(variant) = variant {
+                    if let Some(variant) = to_variant {
                         write!(indent, "\x1b[m::\x1b[1m{variant}")?;
                     }
                 }
@@ -101,35 +163,92 @@ impl<'mem, 'facet> Display for Diff<'mem, 'facet> {
                     } => {
                         writeln!(indent, "\x1b[m {{")?;
                         for (field, update) in updates {
-                            writeln!(indent, "{field}: {update}")?;
+                            writeln!(indent, "{field}: {}", self.nested(update))?;
                         }
 
                         for (field, value) in deletions {
-                            writeln!(
-                                indent,
-                                "\x1b[31m{field}: {}\x1b[m",
-                                printer.format_peek(*value)
-                            )?;
+                            writeln!(indent, "\x1b[31m{field}: {}\x1b[m", limits.format(*value))?;
                         }
 
                         for (field, value) in insertions {
-                            writeln!(
-                                indent,
-                                "\x1b[32m{field}: {}\x1b[m",
-                                printer.format_peek(*value)
-                            )?;
+                            writeln!(indent, "\x1b[32m{field}: {}\x1b[m", limits.format(*value))?;
                         }
 
                         f.write_str("}")
                     }
+                    // The value in a `Some`, an `Ok` or an `Err` is not labeled, like in paths
+                    Value::Tuple { updates } if diff::is_wrapper(from) => {
+                        writeln!(indent, "\x1b[m (")?;
+                        for (_, update) in &updates.updates {
+                            writeln!(indent, "{}", self.nested(update))?;
+                        }
+                        f.write_str(")")
+                    }
                     Value::Tuple { updates } => {
                         writeln!(indent, "\x1b[m (")?;
-                        write!(indent, "{updates}")?;
+                        updates.write_labeled(&mut indent, limits, |index| format!(".{index}"))?;
                         f.write_str(")")
                     }
                 }
             }
-            Diff::Sequence { from, to, updates } => {
+            Diff::Map { from, to, updates } => {
+                write!(f, "\x1b[1m")?;
+                from.write_type_name(f, TypeNameOpts::infinite())?;
+                write!(f, "\x1b[m")?;
+
+                if from.id != to.id {
+                    write!(f, " => \x1b[1m")?;
+                    to.write_type_name(f, TypeNameOpts::infinite())?;
+                    write!(f, "\x1b[m")?;
+                }
+
+                let mut indent = PadAdapter {
+                    fmt: f,
+                    on_newline: false,
+                };
+
+                writeln!(indent, " {{")?;
+                for (key, update) in &updates.updates {
+                    writeln!(indent, "{key}: {}", self.nested(update))?;
+                }
+
+                for (key, value) in &updates.deletions {
+                    writeln!(indent, "\x1b[31m{key}: {}\x1b[m", limits.format(*value))?;
+                }
+
+                for (key, value) in &updates.insertions {
+                    writeln!(indent, "\x1b[32m{key}: {}\x1b[m", limits.format(*value))?;
+                }
+
+                f.write_str("}")
+            }
+            Diff::Presence {
+                from,
+                to,
+                value,
+                present,
+            } => {
+                let (from_variant, to_variant, color) = if *present {
+                    ("None", "Some", "\x1b[32m")
+                } else {
+                    ("Some", "None", "\x1b[31m")
+                };
+
+                write!(f, "\x1b[1m")?;
+                from.write_type_name(f, TypeNameOpts::infinite())?;
+                write!(f, "\x1b[m::\x1b[1m{from_variant}\x1b[m => \x1b[1m")?;
+                to.write_type_name(f, TypeNameOpts::infinite())?;
+                writeln!(f, "\x1b[m::\x1b[1m{to_variant}\x1b[m (")?;
+
+                let mut indent = PadAdapter {
+                    fmt: f,
+                    on_newline: true,
+                };
+
+                writeln!(indent, "{color}{}\x1b[m", limits.format(*value))?;
+                f.write_str(")")
+            }
+            Diff::Array { from, to, updates } => {
                 write!(f, "\x1b[1m")?;
                 from.write_type_name(f, TypeNameOpts::infinite())?;
                 write!(f, "\x1b[m")?;
@@ -140,60 +259,142 @@ impl<'mem, 'facet> Display for Diff<'mem, 'facet> {
                     write!(f, "\x1b[m")?;
                 }
 
+                if updates.resized() {
+                    let (from_len, to_len) = updates.lengths();
+                    write!(f, " \x1b[1;33m(length {from_len} => {to_len})\x1b[m")?;
+                }
+
                 let mut indent = PadAdapter {
                     fmt: f,
                     on_newline: false,
                 };
 
                 writeln!(indent, " [")?;
-                write!(indent, "{updates}")?;
+                if hexdump::is_bytes(from) && hexdump::is_bytes(to) {
+                    hexdump::write(&mut indent, self.diff)?;
+                } else {
+                    updates.write_labeled(&mut indent, limits, |index| format!("[{index}]"))?;
+                }
                 write!(f, "]")
             }
-        }
-    }
-}
+            Diff::BackReference { from, to, cycle } => {
+                write!(f, "\x1b[1m")?;
+                from.type_name(f, TypeNameOpts::infinite())?;
+                f.write_str("\x1b[m")?;
 
-impl<'mem, 'facet> Display for Updates<'mem, 'facet> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(update) = &self.0.first {
-            update.fmt(f)?;
-        }
+                if from.shape().id != to.shape().id {
+                    f.write_str(" => \x1b[1m")?;
+                    to.type_name(f, TypeNameOpts::infinite())?;
+                    f.write_str("\x1b[m")?;
+                }
 
-        let printer = PrettyPrinter::default().with_colors(false);
+                if *cycle {
+                    f.write_str(" \x1b[1;36m<back-reference: cycle>\x1b[m")
+                } else {
+                    f.write_str(" \x1b[1;36m<back-reference: compared elsewhere>\x1b[m")
+                }
+            }
+            Diff::Inaccessible { from, to } => {
+                write!(f, "\x1b[1m")?;
+                from.type_name(f, TypeNameOpts::infinite())?;
+                f.write_str("\x1b[m")?;
 
-        for (values, update) in &self.0.values {
-            for value in values {
-                writeln!(f, "{}", printer.format_peek(*value))?;
+                if from.shape().id != to.shape().id {
+                    f.write_str(" => \x1b[1m")?;
+                    to.type_name(f, TypeNameOpts::infinite())?;
+                    f.write_str("\x1b[m")?;
+                }
+
+                f.write_str(" \x1b[1;36m<inaccessible>\x1b[m")
             }
-            update.fmt(f)?;
-        }
+            Diff::Redacted { .. } => {
+                f.write_str("\x1b[31m<redacted>\x1b[m → \x1b[32m<redacted (changed)>\x1b[m")
+            }
+            Diff::Outcome {
+                from,
+                to,
+                from_value,
+                to_value,
+                ok,
+            } => {
+                let (from_variant, to_variant) = if *ok { ("Err", "Ok") } else { ("Ok", "Err") };
 
-        if let Some(values) = &self.0.last {
-            for value in values {
-                writeln!(f, "{}", printer.format_peek(*value))?;
+                write!(f, "\x1b[1m")?;
+                from.write_type_name(f, TypeNameOpts::infinite())?;
+                write!(f, "\x1b[m::\x1b[1m{from_variant}\x1b[m => \x1b[1m")?;
+                to.write_type_name(f, TypeNameOpts::infinite())?;
+                writeln!(
+                    f,
+                    "\x1b[m::\x1b[1m{to_variant}\x1b[m \x1b[1;33m(outcome flipped)\x1b[m ("
+                )?;
+
+                let mut indent = PadAdapter {
+                    fmt: f,
+                    on_newline: true,
+                };
+
+                writeln!(indent, "\x1b[31m{}\x1b[m", limits.format(*from_value))?;
+                writeln!(indent, "\x1b[32m{}\x1b[m", limits.format(*to_value))?;
+                f.write_str(")")
             }
-        }
+            Diff::Sequence { from, to, updates } => {
+                write!(f, "\x1b[1m")?;
+                from.write_type_name(f, TypeNameOpts::infinite())?;
+                write!(f, "\x1b[m")?;
 
-        Ok(())
-    }
-}
+                if from.id != to.id {
+                    write!(f, " => \x1b[1m")?;
+                    to.write_type_name(f, TypeNameOpts::infinite())?;
+                    write!(f, "\x1b[m")?;
+                }
 
-impl<'mem, 'facet> Display for UpdatesGroup<'mem, 'facet> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(update) = &self.0.first {
-            update.fmt(f)?;
-        }
+                let mut indent = PadAdapter {
+                    fmt: f,
+                    on_newline: false,
+                };
 
-        for (values, update) in &self.0.values {
-            for value in values {
-                writeln!(f, "{value}")?;
+                writeln!(indent, " [")?;
+                if hexdump::is_bytes(from) && hexdump::is_bytes(to) {
+                    hexdump::write(&mut indent, self.diff)?;
+                } else {
+                    updates.write_tree(&mut indent, limits)?;
+                }
+                write!(f, "]")
             }
-            update.fmt(f)?;
         }
+    }
+}
 
-        if let Some(values) = &self.0.last {
-            for value in values {
-                writeln!(f, "{value}")?;
+impl<'mem, 'facet> Display for Updates<'mem, 'facet> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_tree(f, &Limits::default())
+    }
+}
+
+impl<'mem, 'facet> Updates<'mem, 'facet> {
+    /// Writes the elements, the changed ones being labeled with their indices, e.g. `[41] -` or
+    /// `[41→42] ~`.
+    fn write_tree(&self, f: &mut impl Write, limits: &Limits) -> std::fmt::Result {
+        for change in self.changes() {
+            match change {
+                ElementChange::Kept { value, .. } => writeln!(f, "{}", limits.format(value))?,
+                ElementChange::Removed { from, value } => {
+                    writeln!(f, "\x1b[31m[{from}] - {}\x1b[m", limits.format(value))?
+                }
+                ElementChange::Added { to, value } => {
+                    writeln!(f, "\x1b[32m[{to}] + {}\x1b[m", limits.format(value))?
+                }
+                ElementChange::Replaced { from, to, diff } => {
+                    let diff = Tree {
+                        diff,
+                        limits: *limits,
+                    };
+
+                    match from == to {
+                        true => writeln!(f, "[{from}] ~ {diff}")?,
+                        false => writeln!(f, "[{from}→{to}] ~ {diff}")?,
+                    }
+                }
             }
         }
 
@@ -201,16 +402,59 @@ impl<'mem, 'facet> Display for UpdatesGroup<'mem, 'facet> {
     }
 }
 
-impl<'mem, 'facet> Display for ReplaceGroup<'mem, 'facet> {
+impl<'mem, 'facet> Display for Positional<'mem, 'facet> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let printer = PrettyPrinter::default().with_colors(false);
+        self.write_labeled(f, &Limits::default(), |index| format!("[{index}]"))
+    }
+}
+
+impl<'mem, 'facet> Positional<'mem, 'facet> {
+    /// Writes the changed positions, each of them labeled by `label`, e.g. `[3]` or `.0`.
+    fn write_labeled(
+        &self,
+        f: &mut impl Write,
+        limits: &Limits,
+        label: impl Fn(usize) -> String,
+    ) -> std::fmt::Result {
+        for (index, element) in self.common() {
+            let Common::Changed(diff) = element else {
+                continue;
+            };
+            let label = label(index);
+
+            // Print small changes on a single line, e.g. `[3]: 1.0 → 2.0`
+            if let Diff::Replace { from, to } = diff {
+                let (from, to) = limits.format_pair(*from, *to);
+
+                if !from.contains('\n') && !to.contains('\n') {
+                    writeln!(f, "{label}: \x1b[31m{from}\x1b[m → \x1b[32m{to}\x1b[m")?;
+                    continue;
+                }
+            }
+
+            let diff = Tree {
+                diff,
+                limits: *limits,
+            };
+            writeln!(f, "{label}: {diff}")?;
+        }
 
-        for remove in &self.removals {
-            writeln!(f, "\x1b[31m{}\x1b[m", printer.format_peek(*remove))?;
+        for (index, value) in &self.deletions {
+            writeln!(
+                f,
+                "\x1b[31m{}: {}\x1b[m",
+                label(*index),
+                limits.format(*value)
+            )?;
         }
 
-        for add in &self.additions {
-            writeln!(f, "\x1b[32m{}\x1b[m", printer.format_peek(*add))?;
+        for (index, value) in &self.insertions {
+            writeln!(
+                f,
+                "\x1b[32m{}: {}\x1b[m",
+                label(*index),
+                limits.format(*value)
+            )?;
         }
 
         Ok(())
@@ -0,0 +1,138 @@
+use facet_reflect::Peek;
+
+use crate::{diff, pretty};
+
+/// Limits on the size of the values in a rendering of a diff.
+///
+/// Values that exceed them are elided in the middle, with a marker saying how much was left out.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Limits {
+    /// The maximum number of characters of each line of a value
+    pub(crate) max_len: Option<usize>,
+
+    /// The maximum number of lines of a value, once wrapped
+    pub(crate) max_lines: Option<usize>,
+
+    /// The width at which the lines of a value are wrapped
+    pub(crate) width: Option<usize>,
+}
+
+impl Limits {
+    /// Formats a value, eliding it if it is too large.
+    pub(crate) fn format(&self, peek: Peek) -> String {
+        self.elide(pretty::format(peek))
+    }
+
+    /// Formats a value and the one that replaces it.
+    ///
+    /// Strings that are too long are cut around the region that differs between them, rather than
+    /// in their middle.
+    pub(crate) fn format_pair(&self, from: Peek, to: Peek) -> (String, String) {
+        match (self.max_len, diff::as_str(from), diff::as_str(to)) {
+            (Some(max_len), Some(from), Some(to)) => {
+                let (from, to) = around_change(from, to, max_len);
+                (self.wrap(from), self.wrap(to))
+            }
+            _ => (self.format(from), self.format(to)),
+        }
+    }
+
+    /// Elides a formatted value if it is too large, line by line and then as a whole.
+    pub(crate) fn elide(&self, text: String) -> String {
+        let text = match self.max_len {
+            Some(max_len) => text
+                .split('\n')
+                .map(|line| elide_chars(line, max_len))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => text,
+        };
+
+        self.wrap(text)
+    }
+
+    /// Wraps the lines of a value, and elides the ones in the middle if there are too many.
+    fn wrap(&self, text: String) -> String {
+        if self.width.is_none() && self.max_lines.is_none() {
+            return text;
+        }
+
+        let mut lines = Vec::new();
+        for line in text.split('\n') {
+            match self.width {
+                Some(width) if width > 0 && line.chars().count() > width => {
+                    let chars = line.chars().collect::<Vec<_>>();
+                    lines.extend(chars.chunks(width).map(String::from_iter));
+                }
+                _ => lines.push(line.to_string()),
+            }
+        }
+
+        if let Some(max_lines) = self.max_lines {
+            if lines.len() > max_lines {
+                let hidden = lines.len() - max_lines;
+                let head = max_lines.div_ceil(2);
+
+                lines.splice(head..head + hidden, [format!("[… {hidden} lines …]")]);
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Keeps the first and last characters of a text, so that at most `max_len` of them are left.
+fn elide_chars(text: &str, max_len: usize) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    if chars.len() <= max_len {
+        return text.to_string();
+    }
+
+    let head = max_len.div_ceil(2);
+    let tail = chars.len() - (max_len - head);
+
+    format!(
+        "{}[… {} characters …]{}",
+        String::from_iter(&chars[..head]),
+        tail - head,
+        String::from_iter(&chars[tail..])
+    )
+}
+
+/// Keeps at most `max_len` characters of two strings, centered on the region where they differ.
+fn around_change(from: &str, to: &str, max_len: usize) -> (String, String) {
+    let from = from.chars().collect::<Vec<_>>();
+    let to = to.chars().collect::<Vec<_>>();
+
+    let prefix = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let suffix = from[prefix..]
+        .iter()
+        .rev()
+        .zip(to[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let window = |chars: &[char]| {
+        if chars.len() <= max_len {
+            return String::from_iter(chars);
+        }
+
+        let center = (prefix + chars.len() - suffix) / 2;
+        let start = center
+            .saturating_sub(max_len / 2)
+            .min(chars.len() - max_len);
+        let end = start + max_len;
+
+        let mut text = String::new();
+        if start > 0 {
+            text.push_str(&format!("[… {start} characters …]"));
+        }
+        text.extend(&chars[start..end]);
+        if end < chars.len() {
+            text.push_str(&format!("[… {} characters …]", chars.len() - end));
+        }
+        text
+    };
+
+    (window(&from), window(&to))
+}
@@ -0,0 +1,229 @@
+use std::fmt::Write;
+
+use facet::{Def, PointerType, Shape, Type};
+use facet_reflect::Peek;
+
+use crate::{Diff, DiffVisitor};
+
+/// The number of bytes on each side of a row
+const ROW: usize = 8;
+
+/// The number of unchanged rows that are shown around a change, the others are elided
+const CONTEXT: usize = 2;
+
+/// Whether the shape is a sequence of bytes, e.g. `Vec<u8>`, `[u8; N]`, `&[u8]` or `Bytes`.
+pub(crate) fn is_bytes(shape: &'static Shape) -> bool {
+    let element = match (shape.def, shape.ty) {
+        (Def::List(def), _) => def.t(),
+        (Def::Array(def), _) => def.t(),
+        (Def::Slice(def), _) => def.t(),
+        (_, Type::Pointer(PointerType::Reference(pointer))) => match (pointer.target)().def {
+            Def::Slice(def) => def.t(),
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    element.is_type::<u8>()
+}
+
+/// A column of the dump, with a byte on either or both sides.
+#[derive(Clone, Copy)]
+enum Cell {
+    Keep(u8),
+    Remove(u8),
+    Add(u8),
+    Replace(u8, u8),
+
+    /// A byte on both sides whose value is not in the diff, e.g. when a custom differ found the two
+    /// bytes equal or reported them as something else than a replacement
+    Unknown {
+        changed: bool,
+    },
+}
+
+impl Cell {
+    /// The byte on the `from` side, which is `Some(None)` if its value is unknown
+    fn before(self) -> Option<Option<u8>> {
+        match self {
+            Cell::Keep(byte) | Cell::Remove(byte) | Cell::Replace(byte, _) => Some(Some(byte)),
+            Cell::Unknown { .. } => Some(None),
+            Cell::Add(_) => None,
+        }
+    }
+
+    /// The byte on the `to` side, which is `Some(None)` if its value is unknown
+    fn after(self) -> Option<Option<u8>> {
+        match self {
+            Cell::Keep(byte) | Cell::Add(byte) | Cell::Replace(_, byte) => Some(Some(byte)),
+            Cell::Unknown { .. } => Some(None),
+            Cell::Remove(_) => None,
+        }
+    }
+
+    fn is_changed(self) -> bool {
+        match self {
+            Cell::Keep(_) => false,
+            Cell::Unknown { changed } => changed,
+            _ => true,
+        }
+    }
+}
+
+/// Collects the cells of a diff between two byte sequences.
+#[derive(Default)]
+struct Cells(Vec<Cell>);
+
+impl Cells {
+    fn push(&mut self, cell: Cell) {
+        // Pair up removals and additions, so that substituted bytes end up on the same row
+        if let Cell::Add(to) = cell {
+            let unpaired = self
+                .0
+                .iter()
+                .rev()
+                .take_while(|cell| matches!(cell, Cell::Remove(_) | Cell::Replace(..)))
+                .filter(|cell| matches!(cell, Cell::Remove(_)))
+                .count();
+
+            if unpaired > 0 {
+                let index = self.0.len() - unpaired;
+                if let Cell::Remove(from) = self.0[index] {
+                    self.0[index] = Cell::Replace(from, to);
+                    return;
+                }
+            }
+        }
+
+        self.0.push(cell);
+    }
+}
+
+fn byte(peek: Peek) -> u8 {
+    peek.get::<u8>().copied().unwrap_or_default()
+}
+
+impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for Cells {
+    fn element_kept(&mut self, value: Peek<'mem, 'facet>) {
+        self.push(Cell::Keep(byte(value)));
+    }
+
+    fn element_added(&mut self, value: Peek<'mem, 'facet>) {
+        self.push(Cell::Add(byte(value)));
+    }
+
+    fn element_removed(&mut self, value: Peek<'mem, 'facet>) {
+        self.push(Cell::Remove(byte(value)));
+    }
+
+    fn element_replaced(&mut self, diff: &Diff<'mem, 'facet>) {
+        // Every element takes a cell, so that the offsets of the following ones stay right
+        match diff {
+            Diff::Replace { from, to } => self.push(Cell::Replace(byte(*from), byte(*to))),
+            diff => self.push(Cell::Unknown {
+                changed: !diff.is_equal(),
+            }),
+        }
+    }
+}
+
+/// Writes the diff between two byte sequences as a side-by-side hex and ASCII dump, with offsets.
+///
+/// The `from` bytes are on the left and the `to` bytes on the right, changed bytes are highlighted.
+pub(crate) fn write(f: &mut impl Write, diff: &Diff) -> std::fmt::Result {
+    let mut cells = Cells::default();
+    diff.walk(&mut cells);
+
+    let rows = cells.0.chunks(ROW).collect::<Vec<_>>();
+    let changed = |row: &[Cell]| row.iter().any(|cell| cell.is_changed());
+
+    let mut from_offset = 0;
+    let mut to_offset = 0;
+    let mut elided = 0;
+
+    for (i, row) in rows.iter().enumerate() {
+        let near_change = rows[i.saturating_sub(CONTEXT)..(i + CONTEXT + 1).min(rows.len())]
+            .iter()
+            .any(|row| changed(row));
+
+        if near_change {
+            if elided > 0 {
+                writeln!(f, "\x1b[2m... {elided} unchanged rows\x1b[m")?;
+                elided = 0;
+            }
+
+            write_side(f, from_offset, row, Cell::before, "\x1b[31m")?;
+            f.write_str("  ")?;
+            write_side(f, to_offset, row, Cell::after, "\x1b[32m")?;
+            f.write_char('\n')?;
+        } else {
+            elided += 1;
+        }
+
+        from_offset += row.iter().filter_map(|cell| cell.before()).count();
+        to_offset += row.iter().filter_map(|cell| cell.after()).count();
+    }
+
+    if elided > 0 {
+        writeln!(f, "\x1b[2m... {elided} unchanged rows\x1b[m")?;
+    }
+
+    Ok(())
+}
+
+/// Writes one side of a row, e.g. `00000010  48 65 6c 6c 6f 20 77 6f  |Hello wo|`
+fn write_side(
+    f: &mut impl Write,
+    offset: usize,
+    row: &[Cell],
+    side: fn(Cell) -> Option<Option<u8>>,
+    color: &str,
+) -> std::fmt::Result {
+    write!(f, "{offset:08x} ")?;
+
+    for cell in row {
+        let hex = match side(*cell) {
+            Some(Some(byte)) => format!("{byte:02x}"),
+            Some(None) => "..".into(),
+            None => {
+                f.write_str("   ")?;
+                continue;
+            }
+        };
+
+        match cell.is_changed() {
+            true => write!(f, " {color}{hex}\x1b[m")?,
+            false => write!(f, " {hex}")?,
+        }
+    }
+
+    for _ in row.len()..ROW {
+        f.write_str("   ")?;
+    }
+
+    f.write_str("  |")?;
+
+    for cell in row {
+        let Some(byte) = side(*cell) else {
+            f.write_char(' ')?;
+            continue;
+        };
+
+        let char = match byte {
+            Some(byte) if byte.is_ascii_graphic() || byte == b' ' => byte as char,
+            _ => '.',
+        };
+
+        if cell.is_changed() {
+            write!(f, "{color}{char}\x1b[m")?;
+        } else {
+            f.write_char(char)?;
+        }
+    }
+
+    for _ in row.len()..ROW {
+        f.write_char(' ')?;
+    }
+
+    f.write_char('|')
+}
@@ -0,0 +1,445 @@
+use std::fmt::{Display, Write};
+
+use facet::Shape;
+use facet_reflect::Peek;
+
+use crate::{
+    Diff, DiffVisitor, diff,
+    owned::type_name,
+    pretty::format,
+    tracking::{Elements, Tracker},
+};
+
+/// A rendering of a [`Diff`] as static HTML, made of nested `<details>` and `<ul>` elements.
+///
+/// Every item has one of the `added`, `removed`, `changed` or `unchanged` classes, and runs of
+/// unchanged items are collapsed into a `<details>` element of their own, so the output works
+/// without any JavaScript. It starts with a small stylesheet for these classes, cf.
+/// [`Html::stylesheet`].
+pub struct Html<'a, 'mem, 'facet> {
+    diff: &'a Diff<'mem, 'facet>,
+    stylesheet: bool,
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Renders this diff as HTML, cf. [`Html`].
+    pub fn html(&self) -> Html<'_, 'mem, 'facet> {
+        Html {
+            diff: self,
+            stylesheet: true,
+        }
+    }
+}
+
+impl<'a, 'mem, 'facet> Html<'a, 'mem, 'facet> {
+    /// Sets whether a `<style>` element for the classes of the items is included, which it is by
+    /// default. Without it, the output is meant to be styled by the page it is embedded in.
+    pub fn stylesheet(mut self, stylesheet: bool) -> Self {
+        self.stylesheet = stylesheet;
+        self
+    }
+}
+
+const STYLESHEET: &str = "<style>
+.facet-diff { font-family: monospace; }
+.facet-diff ul { list-style: none; margin: 0; padding-left: 1.5em; }
+.facet-diff summary { cursor: pointer; }
+.facet-diff code { white-space: pre-wrap; }
+.facet-diff .added { color: #1a7f37; background: #e6ffec; text-decoration: none; }
+.facet-diff .removed { color: #cf222e; background: #ffebe9; }
+.facet-diff .changed > summary { font-weight: bold; }
+.facet-diff .unchanged { color: #6e7781; }
+.facet-diff .note { color: #0969da; }
+</style>
+";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+impl Class {
+    fn name(self) -> &'static str {
+        match self {
+            Class::Added => "added",
+            Class::Removed => "removed",
+            Class::Changed => "changed",
+            Class::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// A rendered `<li>` element.
+struct Item {
+    class: Class,
+    html: String,
+}
+
+/// A struct, map or sequence, whose items are rendered as a list once they are all collected.
+struct Frame {
+    summary: String,
+
+    /// Whether the items are fields or entries, i.e. the frame is a struct, an enum variant or a
+    /// map rather than a sequence
+    keyed: bool,
+
+    items: Vec<Item>,
+}
+
+struct Renderer {
+    tracker: Tracker,
+    frames: Vec<Frame>,
+
+    /// The contents being rendered for each nested item, the first one is the whole diff
+    slots: Vec<String>,
+}
+
+/// Escapes the characters that have a meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn code(text: &str) -> String {
+    format!("<code>{}</code>", escape(text))
+}
+
+fn added(text: &str) -> String {
+    format!("<ins class=\"added\">{}</ins>", code(text))
+}
+
+fn removed(text: &str) -> String {
+    format!("<del class=\"removed\">{}</del>", code(text))
+}
+
+/// The name of a type, along with its variant if it is an enum.
+fn summary(shape: &'static Shape, variant: Option<&str>) -> String {
+    match variant {
+        Some(variant) => format!("{}::{variant}", type_name(shape)),
+        None => type_name(shape),
+    }
+}
+
+impl Renderer {
+    fn write(&mut self, html: &str) {
+        if let Some(slot) = self.slots.last_mut() {
+            slot.push_str(html);
+        }
+    }
+
+    /// Renders an item labeled with the current path segment, whose contents are written by `f`.
+    fn item(&mut self, class: Class, f: impl FnOnce(&mut Self)) {
+        let label = self
+            .tracker
+            .last()
+            .map(|segment| segment.label())
+            .unwrap_or_default();
+
+        self.slots.push(String::new());
+        f(self);
+        let contents = self.slots.pop().unwrap_or_default();
+        self.tracker.pop();
+
+        let html = format!(
+            "<li class=\"{}\"><span class=\"path\">{}</span>: {contents}</li>\n",
+            class.name(),
+            escape(&label)
+        );
+
+        match self.frames.last_mut() {
+            Some(frame) => frame.items.push(Item { class, html }),
+            None => self.write(&html),
+        }
+    }
+
+    fn field(&mut self, name: &'static str, class: Class, f: impl FnOnce(&mut Self)) {
+        self.tracker.push_field(name);
+        self.item(class, f);
+    }
+
+    fn entry(&mut self, key: &str, class: Class, f: impl FnOnce(&mut Self)) {
+        self.tracker.push_entry(key);
+        self.item(class, f);
+    }
+
+    /// Renders an element, cf. [`Tracker::push_element`]. Elements without a segment of their own
+    /// are rendered in place.
+    fn element(
+        &mut self,
+        (from, to, changed): (bool, bool, bool),
+        class: Class,
+        f: impl FnOnce(&mut Self),
+    ) {
+        if self.tracker.push_element(from, to, changed) {
+            self.item(class, f);
+        } else {
+            f(self);
+            self.tracker.pop();
+        }
+    }
+
+    /// Renders an unchanged value, unless it is sensitive, since those are not shown either.
+    fn unchanged(&mut self, value: Peek) {
+        match self.tracker.is_sensitive() {
+            true => self.write(&code("<redacted>")),
+            false => self.write(&code(&format(value))),
+        }
+    }
+
+    fn enter(
+        &mut self,
+        (from, to): (&'static Shape, &'static Shape),
+        (from_variant, to_variant): (Option<&'static str>, Option<&'static str>),
+        keyed: bool,
+    ) {
+        let mut summary = summary(from, from_variant);
+        if from.id != to.id || from_variant != to_variant {
+            summary = format!("{summary} → {}", self::summary(to, to_variant));
+        }
+
+        if keyed {
+            self.tracker.enter((from, to), (from_variant, to_variant));
+        }
+
+        self.frames.push(Frame {
+            summary,
+            keyed,
+            items: Vec::new(),
+        });
+    }
+
+    /// Renders the items of the current frame, with the runs of unchanged items collapsed.
+    fn exit(&mut self) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+
+        if frame.keyed {
+            self.tracker.exit();
+        }
+
+        let mut html = format!(
+            "<details open class=\"changed\"><summary>{}</summary>\n<ul>\n",
+            escape(&frame.summary)
+        );
+
+        for run in frame
+            .items
+            .chunk_by(|a, b| (a.class == Class::Unchanged) == (b.class == Class::Unchanged))
+        {
+            if run[0].class != Class::Unchanged {
+                html.extend(run.iter().map(|item| item.html.as_str()));
+                continue;
+            }
+
+            let _ = write!(
+                html,
+                "<li class=\"unchanged\"><details><summary>{} unchanged</summary>\n<ul>\n",
+                run.len()
+            );
+            html.extend(run.iter().map(|item| item.html.as_str()));
+            html.push_str("</ul>\n</details></li>\n");
+        }
+
+        html.push_str("</ul>\n</details>");
+        self.write(&html);
+    }
+}
+
+impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for Renderer {
+    fn enter_struct(&mut self, from: &'static Shape, to: &'static Shape) {
+        self.enter((from, to), (None, None), true);
+    }
+
+    fn exit_struct(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.exit();
+    }
+
+    fn enter_variant(
+        &mut self,
+        from: &'static Shape,
+        to: &'static Shape,
+        from_variant: &'static str,
+        to_variant: &'static str,
+    ) {
+        // The value in a `Some`, an `Ok` or an `Err` is rendered in place of its wrapper
+        if !diff::is_wrapper(from) {
+            self.enter((from, to), (Some(from_variant), Some(to_variant)), true);
+        }
+    }
+
+    fn exit_variant(
+        &mut self,
+        from: &'static Shape,
+        _to: &'static Shape,
+        _from_variant: &'static str,
+        _to_variant: &'static str,
+    ) {
+        if !diff::is_wrapper(from) {
+            self.exit();
+        }
+    }
+
+    fn field_changed(&mut self, field: &'static str, diff: &Diff<'mem, 'facet>) {
+        self.field(field, Class::Changed, |html| diff.walk(html));
+    }
+
+    fn field_unchanged(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        self.field(field, Class::Unchanged, |html| html.unchanged(value));
+    }
+
+    fn field_added(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        self.field(field, Class::Added, |html| {
+            html.write(&added(&format(value)))
+        });
+    }
+
+    fn field_removed(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        self.field(field, Class::Removed, |html| {
+            html.write(&removed(&format(value)))
+        });
+    }
+
+    fn enter_map(&mut self, from: &'static Shape, to: &'static Shape) {
+        self.enter((from, to), (None, None), true);
+    }
+
+    fn exit_map(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.exit();
+    }
+
+    fn entry_changed(&mut self, key: &str, diff: &Diff<'mem, 'facet>) {
+        self.entry(key, Class::Changed, |html| diff.walk(html));
+    }
+
+    fn entry_unchanged(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+        self.entry(key, Class::Unchanged, |html| html.unchanged(value));
+    }
+
+    fn entry_added(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+        self.entry(key, Class::Added, |html| html.write(&added(&format(value))));
+    }
+
+    fn entry_removed(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+        self.entry(key, Class::Removed, |html| {
+            html.write(&removed(&format(value)))
+        });
+    }
+
+    fn enter_sequence(&mut self, from: &'static Shape, to: &'static Shape) {
+        // The fields of tuples and tuple variants are items of the struct or variant itself
+        if self.tracker.enter_sequence(from) == Elements::Indexed {
+            self.enter((from, to), (None, None), false);
+        }
+    }
+
+    fn exit_sequence(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        if self.tracker.exit_sequence() == Some(Elements::Indexed) {
+            self.exit();
+        }
+    }
+
+    fn element_kept(&mut self, value: Peek<'mem, 'facet>) {
+        self.element((true, true, false), Class::Unchanged, |html| {
+            html.unchanged(value)
+        });
+    }
+
+    fn element_added(&mut self, value: Peek<'mem, 'facet>) {
+        self.element((false, true, false), Class::Added, |html| {
+            html.write(&added(&format(value)))
+        });
+    }
+
+    fn element_removed(&mut self, value: Peek<'mem, 'facet>) {
+        self.element((true, false, false), Class::Removed, |html| {
+            html.write(&removed(&format(value)))
+        });
+    }
+
+    fn element_replaced(&mut self, diff: &Diff<'mem, 'facet>) {
+        self.element((true, true, true), Class::Changed, |html| diff.walk(html));
+    }
+
+    fn became_present(&mut self, value: Peek<'mem, 'facet>) {
+        self.write(&added(&format(value)));
+    }
+
+    fn became_absent(&mut self, value: Peek<'mem, 'facet>) {
+        self.write(&removed(&format(value)));
+    }
+
+    fn back_reference(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>, cycle: bool) {
+        if cycle {
+            self.write("<span class=\"note\">&lt;back-reference: cycle&gt;</span>");
+        } else {
+            self.write("<span class=\"note\">&lt;back-reference: compared elsewhere&gt;</span>");
+        }
+    }
+
+    fn inaccessible(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>) {
+        self.write("<span class=\"note\">&lt;inaccessible&gt;</span>");
+    }
+
+    fn redacted(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.write(&format!(
+            "{} → {}",
+            removed("<redacted>"),
+            added("<redacted (changed)>")
+        ));
+    }
+
+    fn outcome_flipped(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>, _ok: bool) {
+        self.write(&format!(
+            "{} → {}",
+            removed(&format(from)),
+            added(&format(to))
+        ));
+    }
+
+    fn leaf_replace(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {
+        self.write(&format!(
+            "{} → {}",
+            removed(&format(from)),
+            added(&format(to))
+        ));
+    }
+}
+
+impl<'a, 'mem, 'facet> Display for Html<'a, 'mem, 'facet> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut renderer = Renderer {
+            tracker: Tracker::default(),
+            frames: Vec::new(),
+            slots: vec![String::new()],
+        };
+        self.diff.walk(&mut renderer);
+        let body = renderer.slots.pop().unwrap_or_default();
+
+        if self.stylesheet {
+            f.write_str(STYLESHEET)?;
+        }
+
+        f.write_str("<div class=\"facet-diff\">\n")?;
+        if body.is_empty() {
+            f.write_str("<p class=\"unchanged\">equal</p>")?;
+        } else {
+            f.write_str(&body)?;
+        }
+        f.write_str("\n</div>\n")
+    }
+}
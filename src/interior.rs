@@ -0,0 +1,66 @@
+use std::fmt::Display;
+
+use facet::{Def, KnownPointer, PointerFlags, Shape, TypeNameOpts};
+
+/// What to do with a value behind a lock or a cell, e.g. a `Mutex` or a `RefCell`, that cannot be
+/// accessed.
+///
+/// Cells are compared like any other pointer when their shape lets us borrow the inner value, this
+/// policy only applies when it does not. Locks are never locked, since that could block on a lock
+/// held elsewhere or find it poisoned, so the policy always applies to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BorrowPolicy {
+    /// Consider the values equal
+    Skip,
+
+    /// Report the values as inaccessible in the diff, cf. [`Diff::Inaccessible`](crate::Diff::Inaccessible)
+    #[default]
+    Report,
+
+    /// Fail the whole diff, cf. [`Differ::diff`](crate::Differ::diff)
+    Error,
+}
+
+/// An error that prevented a diff from being computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DiffError {
+    /// A value of this shape is behind a lock or a cell that cannot be accessed
+    Inaccessible(&'static Shape),
+}
+
+impl Display for DiffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffError::Inaccessible(shape) => {
+                f.write_str("cannot access the value inside ")?;
+                shape.write_type_name(f, TypeNameOpts::infinite())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+/// Whether the shape has interior mutability, i.e. it is a lock or a cell.
+pub(crate) fn is_interior(shape: &'static Shape) -> bool {
+    let Def::Pointer(def) = shape.def else {
+        return false;
+    };
+
+    is_lock(shape)
+        || matches!(
+            def.known,
+            Some(KnownPointer::Cell | KnownPointer::RefCell | KnownPointer::OnceCell)
+        )
+}
+
+/// Whether the shape is a lock, e.g. a `Mutex` or an `RwLock`.
+pub(crate) fn is_lock(shape: &'static Shape) -> bool {
+    let Def::Pointer(def) = shape.def else {
+        return false;
+    };
+
+    def.flags.contains(PointerFlags::LOCK)
+        || matches!(def.known, Some(KnownPointer::Mutex | KnownPointer::RwLock))
+}
@@ -2,15 +2,1442 @@
 #![forbid(unsafe_code)]
 #![doc = include_str!("../README.md")]
 
+mod compact;
+mod context;
 mod diff;
 mod display;
+mod elide;
+mod hexdump;
+mod html;
+mod interior;
+mod maps;
+mod markdown;
+mod options;
+mod owned;
+mod path;
+mod positional;
+mod pretty;
+mod registry;
 mod sequences;
+mod similarity;
+#[cfg(feature = "insta")]
+pub mod snapshot;
+mod stats;
+mod tracking;
+mod unified;
+mod visitor;
 
+pub use compact::Compact;
 pub use diff::Diff;
 pub use diff::FacetDiff;
+pub use display::Tree;
+pub use html::Html;
+pub use interior::{BorrowPolicy, DiffError};
+pub use markdown::Markdown;
+pub use options::{Differ, SequenceAlgorithm};
+pub use owned::{Indexed, OwnedDiff, OwnedPeek, OwnedPositional, OwnedUpdate, OwnedValue};
+pub use registry::Registry;
+pub use sequences::{ElementChange, Updates};
+pub use similarity::similarity;
+pub use stats::DiffStats;
+pub use unified::Unified;
+pub use visitor::DiffVisitor;
 
 #[cfg(test)]
 mod tests {
+    use facet::Facet;
+    use facet_reflect::Peek;
+
+    use crate::{
+        Diff, Differ, ElementChange, FacetDiff, Indexed, OwnedDiff, OwnedPositional, OwnedValue,
+        Registry, SequenceAlgorithm, diff::Value, positional, similarity,
+    };
+
+    #[derive(Facet)]
+    struct Point {
+        x: i32,
+        y: i32,
+        tags: Vec<u8>,
+    }
+
     #[test]
     fn it_works() {}
+
+    #[test]
+    fn owned_diff_round_trips() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2, 3],
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: vec![2, 3, 4],
+        };
+
+        let diff = OwnedDiff::from(&a.diff(&b));
+
+        let json = facet_json::to_string(&diff);
+        assert_eq!(facet_json::from_str::<OwnedDiff>(&json).unwrap(), diff);
+
+        let msgpack = facet_msgpack::to_vec(&diff);
+        assert_eq!(
+            facet_msgpack::from_slice::<OwnedDiff>(&msgpack).unwrap(),
+            diff
+        );
+    }
+
+    #[test]
+    fn inverted_diff_goes_back() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2, 3],
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: vec![2, 3, 4],
+        };
+
+        assert_eq!(
+            OwnedDiff::from(&a.diff(&b).invert()),
+            OwnedDiff::from(&b.diff(&a))
+        );
+    }
+
+    #[test]
+    fn stats_count_changes() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2, 3],
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: vec![2, 3, 4, 5],
+        };
+
+        let stats = a.diff(&b).stats();
+        assert_eq!(stats.changed, 1);
+        assert_eq!(stats.added, 2);
+        assert_eq!(stats.removed, 1);
+        assert_eq!(stats.unchanged, 3);
+        assert_eq!(stats.fields, 2);
+        assert_eq!(stats.elements, 3);
+        assert_eq!(stats.depth, 2);
+        assert_eq!(stats.similarity, 6.0 / 11.0);
+
+        assert_eq!(a.diff(&a).stats().similarity, 1.0);
+
+        // The value in a `Some` is at the depth of the option, and is not an element
+        #[derive(Facet)]
+        struct Wrapper {
+            point: Option<Point>,
+        }
+
+        let a = Wrapper { point: Some(a) };
+        let b = Wrapper {
+            point: Some(Point {
+                x: 1,
+                y: 3,
+                tags: vec![1, 2, 3],
+            }),
+        };
+
+        let stats = a.diff(&b).stats();
+        assert_eq!(stats.changed, 1);
+        assert_eq!(stats.fields, 2);
+        assert_eq!(stats.elements, 0);
+        assert_eq!(stats.depth, 2);
+    }
+
+    #[test]
+    fn similarity_is_weighted_by_leaves() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2, 3, 4],
+        };
+        let same_tags = Point {
+            x: 5,
+            y: 6,
+            tags: vec![1, 2, 3, 4],
+        };
+        let same_coordinates = Point {
+            x: 1,
+            y: 2,
+            tags: vec![5, 6, 7, 8],
+        };
+
+        assert_eq!(similarity(&a, &a), 1.0);
+        assert!(similarity(&a, &same_tags) > similarity(&a, &same_coordinates));
+
+        #[derive(Facet)]
+        struct Empty {}
+        #[derive(Facet)]
+        struct Nothing {}
+
+        assert_eq!(similarity(&Empty {}, &Nothing {}), 1.0);
+        assert_eq!(similarity(&Vec::<u8>::new(), &Vec::<u16>::new()), 1.0);
+        assert_eq!(Empty {}.diff(&Nothing {}).stats().similarity, 1.0);
+    }
+
+    #[test]
+    fn options_are_diffed_structurally() {
+        let a = Some(Point {
+            x: 1,
+            y: 2,
+            tags: vec![],
+        });
+        let b = Some(Point {
+            x: 1,
+            y: 3,
+            tags: vec![],
+        });
+
+        let OwnedDiff::User {
+            value: OwnedValue::Tuple { updates },
+            ..
+        } = OwnedDiff::from(&a.diff(&b))
+        else {
+            panic!("expected a diff of the inner values");
+        };
+        assert!(matches!(
+            updates.updates.as_slice(),
+            [Indexed {
+                index: 0,
+                value: OwnedDiff::User { .. }
+            }]
+        ));
+
+        assert!(matches!(
+            OwnedDiff::from(&None::<Point>.diff(&b)),
+            OwnedDiff::Presence { present: true, .. }
+        ));
+        assert!(matches!(
+            OwnedDiff::from(&a.diff(&None::<Point>)),
+            OwnedDiff::Presence { present: false, .. }
+        ));
+    }
+
+    #[derive(Facet)]
+    #[repr(C)]
+    #[allow(dead_code)]
+    enum Outcome {
+        Ok(Point),
+        Err(String),
+    }
+
+    #[test]
+    fn outcome_flips_are_reported() {
+        let point = |y| Point {
+            x: 1,
+            y,
+            tags: vec![],
+        };
+
+        assert!(matches!(
+            OwnedDiff::from(&Outcome::Ok(point(2)).diff(&Outcome::Ok(point(3)))),
+            OwnedDiff::User {
+                value: OwnedValue::Tuple { .. },
+                ..
+            }
+        ));
+
+        let OwnedDiff::Outcome {
+            from_value,
+            to_value,
+            ok,
+            ..
+        } = OwnedDiff::from(&Outcome::Ok(point(2)).diff(&Outcome::Err("timeout".into())))
+        else {
+            panic!("expected an outcome flip");
+        };
+        assert!(!ok);
+        assert_eq!(from_value.shape, "Point");
+        assert_eq!(to_value.value, "timeout");
+    }
+
+    #[test]
+    fn wrapped_values_are_not_in_paths() {
+        #[derive(Facet)]
+        struct Job {
+            last: Option<Point>,
+            result: Outcome,
+        }
+
+        let job = |y| Job {
+            last: Some(Point {
+                x: 1,
+                y,
+                tags: vec![],
+            }),
+            result: Outcome::Ok(Point {
+                x: 1,
+                y,
+                tags: vec![],
+            }),
+        };
+        let (a, b) = (job(2), job(3));
+
+        let diff = a.diff(&b);
+        assert_eq!(diff.compact().to_string(), "last.y: 2→3, result.y: 2→3");
+        assert!(
+            diff.unified()
+                .colors(false)
+                .to_string()
+                .contains("- last.y: 2\n+ last.y: 3\n")
+        );
+        assert!(
+            diff.markdown()
+                .to_string()
+                .contains("| `result.y` | `2` | `3` |")
+        );
+
+        // Every rendered path can be ignored or included
+        let differ = Differ::new().ignore("last.y").ignore("result.y");
+        assert!(differ.diff(&a, &b).unwrap().is_equal());
+        let differ = Differ::new().include("result.y");
+        assert_eq!(
+            differ.diff(&a, &b).unwrap().compact().to_string(),
+            "result.y: 2→3"
+        );
+    }
+
+    #[test]
+    fn arrays_are_diffed_by_index() {
+        let a = [1.0f32, 0.0, 0.0, 1.0];
+        let b = [1.0f32, 0.0, 0.0, 2.0];
+
+        let diff = OwnedDiff::from(&a.diff(&b));
+        let OwnedDiff::Array {
+            updates:
+                OwnedPositional {
+                    updates,
+                    deletions,
+                    insertions,
+                    unchanged,
+                },
+            ..
+        } = &diff
+        else {
+            panic!("expected an array diff");
+        };
+        assert_eq!(updates.iter().map(|x| x.index).collect::<Vec<_>>(), [3]);
+        assert!(deletions.is_empty() && insertions.is_empty());
+        assert_eq!(unchanged, &[0, 1, 2]);
+
+        let json = facet_json::to_string(&diff);
+        assert_eq!(facet_json::from_str::<OwnedDiff>(&json).unwrap(), diff);
+
+        let a: &[u8] = &[1, 2, 3];
+        let b: &[u8] = &[1, 5];
+        let diff = a.diff(&b);
+        let Diff::Array { updates, .. } = &diff else {
+            panic!("expected an array diff");
+        };
+        assert!(updates.resized());
+        assert_eq!(updates.lengths(), (3, 2));
+        assert_eq!(diff.stats().removed, 1);
+    }
+
+    #[test]
+    fn tuples_are_diffed_by_position() {
+        let Diff::User {
+            value: Value::Tuple { updates },
+            ..
+        } = (1, 2, 3).diff(&(2, 3, 1))
+        else {
+            panic!("expected a tuple diff");
+        };
+        assert_eq!(
+            updates.updates.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+
+        let Diff::User {
+            value: Value::Tuple { updates },
+            ..
+        } = (1, 2).diff(&(1, 2, 3))
+        else {
+            panic!("expected a tuple diff");
+        };
+        assert_eq!(updates.lengths(), (2, 3));
+        assert_eq!(updates.insertions.len(), 1);
+        assert_eq!(updates.unchanged.len(), 2);
+    }
+
+    #[test]
+    fn variant_changes_keep_common_fields() {
+        #[derive(Facet)]
+        #[repr(C)]
+        #[allow(dead_code)]
+        enum State {
+            Running { pid: u32, started: u64 },
+            Stopped { pid: u32, started: u64, code: i32 },
+            Failed { pid: String, started: u64 },
+            Exited { pid: String },
+        }
+
+        #[derive(Facet)]
+        #[repr(C)]
+        #[allow(dead_code)]
+        enum Update {
+            Remove(usize),
+            Add(usize),
+            Move(usize, usize),
+            Clear,
+        }
+
+        let running = State::Running { pid: 1, started: 5 };
+
+        let OwnedDiff::User {
+            from_variant,
+            to_variant,
+            value:
+                OwnedValue::Struct {
+                    updates,
+                    deletions,
+                    insertions,
+                    unchanged,
+                },
+            ..
+        } = OwnedDiff::from(&running.diff(&State::Stopped {
+            pid: 1,
+            started: 5,
+            code: 3,
+        }))
+        else {
+            panic!("expected a diff between the variants");
+        };
+        assert_eq!(from_variant.as_deref(), Some("Running"));
+        assert_eq!(to_variant.as_deref(), Some("Stopped"));
+        assert!(updates.is_empty() && deletions.is_empty());
+        assert_eq!(insertions.keys().collect::<Vec<_>>(), ["code"]);
+        assert_eq!(unchanged, ["pid", "started"]);
+
+        // A field with the same name but another type is removed and added back
+        let failed = State::Failed {
+            pid: "1".into(),
+            started: 6,
+        };
+        let diff = running.diff(&failed);
+        let OwnedDiff::User {
+            value:
+                OwnedValue::Struct {
+                    updates,
+                    deletions,
+                    insertions,
+                    unchanged,
+                },
+            ..
+        } = OwnedDiff::from(&diff)
+        else {
+            panic!("expected a diff between the variants");
+        };
+        assert_eq!(updates.keys().collect::<Vec<_>>(), ["started"]);
+        assert_eq!(deletions["pid"].shape, "u32");
+        assert_eq!(insertions["pid"].shape, "String");
+        assert!(unchanged.is_empty());
+        assert_eq!(
+            diff.unified().colors(false).to_string(),
+            "- pid: 1\n+ pid: 1\n- started: 5\n+ started: 6\n"
+        );
+
+        // Without any field in common, the variants are replaced
+        assert!(matches!(
+            running.diff(&State::Exited { pid: "1".into() }),
+            Diff::Replace { .. }
+        ));
+
+        // Tuple variants
+        let Diff::User {
+            from_variant: Some("Remove"),
+            to_variant: Some("Remove"),
+            value: Value::Tuple { updates },
+            ..
+        } = Update::Remove(0).diff(&Update::Remove(1))
+        else {
+            panic!("expected a diff between the fields");
+        };
+        assert_eq!(
+            updates.updates.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            [0]
+        );
+
+        let diff = Update::Remove(0).diff(&Update::Add(0));
+        assert!(!diff.is_equal());
+        let Diff::User {
+            from_variant: Some("Remove"),
+            to_variant: Some("Add"),
+            value: Value::Tuple { updates },
+            ..
+        } = diff
+        else {
+            panic!("expected a diff between the variants");
+        };
+        assert!(updates.is_empty());
+        assert_eq!(updates.unchanged.len(), 1);
+
+        let Diff::User {
+            value: Value::Tuple { updates },
+            ..
+        } = Update::Remove(0).diff(&Update::Move(0, 1))
+        else {
+            panic!("expected a diff between the variants");
+        };
+        assert_eq!(updates.lengths(), (1, 2));
+        assert_eq!(updates.insertions.len(), 1);
+
+        assert!(matches!(
+            Update::Remove(0).diff(&Update::Clear),
+            Diff::Replace { .. }
+        ));
+    }
+
+    #[test]
+    fn visitors_are_called_in_order() {
+        use facet::Shape;
+
+        use crate::DiffVisitor;
+
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for Recorder {
+            fn enter_struct(&mut self, from: &'static Shape, _to: &'static Shape) {
+                self.0.push(format!("enter_struct {from}"));
+            }
+
+            fn exit_struct(&mut self, from: &'static Shape, _to: &'static Shape) {
+                self.0.push(format!("exit_struct {from}"));
+            }
+
+            fn enter_variant(
+                &mut self,
+                _from: &'static Shape,
+                _to: &'static Shape,
+                from_variant: &'static str,
+                to_variant: &'static str,
+            ) {
+                self.0
+                    .push(format!("enter_variant {from_variant} {to_variant}"));
+            }
+
+            fn exit_variant(
+                &mut self,
+                _from: &'static Shape,
+                _to: &'static Shape,
+                from_variant: &'static str,
+                to_variant: &'static str,
+            ) {
+                self.0
+                    .push(format!("exit_variant {from_variant} {to_variant}"));
+            }
+
+            fn field_changed(&mut self, field: &'static str, diff: &Diff<'mem, 'facet>) {
+                self.0.push(format!("field_changed {field}"));
+                diff.walk(self);
+            }
+
+            fn field_unchanged(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("field_unchanged {field} {value}"));
+            }
+
+            fn field_added(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("field_added {field} {value}"));
+            }
+
+            fn entry_changed(&mut self, key: &str, diff: &Diff<'mem, 'facet>) {
+                self.0.push(format!("entry_changed {key}"));
+                diff.walk(self);
+            }
+
+            fn entry_unchanged(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("entry_unchanged {key} {value}"));
+            }
+
+            fn entry_added(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("entry_added {key} {value}"));
+            }
+
+            fn entry_removed(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("entry_removed {key} {value}"));
+            }
+
+            fn enter_sequence(&mut self, from: &'static Shape, _to: &'static Shape) {
+                self.0.push(format!("enter_sequence {from}"));
+            }
+
+            fn exit_sequence(&mut self, from: &'static Shape, _to: &'static Shape) {
+                self.0.push(format!("exit_sequence {from}"));
+            }
+
+            fn element_kept(&mut self, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("element_kept {value}"));
+            }
+
+            fn element_added(&mut self, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("element_added {value}"));
+            }
+
+            fn element_removed(&mut self, value: Peek<'mem, 'facet>) {
+                self.0.push(format!("element_removed {value}"));
+            }
+
+            fn leaf_replace(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {
+                self.0.push(format!("leaf_replace {from} {to}"));
+            }
+        }
+
+        fn record<'a, T: Facet<'a>>(from: &T, to: &T) -> Vec<String> {
+            let mut recorder = Recorder::default();
+            from.diff(to).walk(&mut recorder);
+            recorder.0
+        }
+
+        #[derive(Facet)]
+        struct Log {
+            lines: Vec<u32>,
+            level: u8,
+        }
+
+        #[derive(Facet)]
+        #[repr(C)]
+        #[allow(dead_code)]
+        enum Event {
+            Started { at: u32 },
+            Stopped { at: u32, code: i32 },
+        }
+
+        assert_eq!(
+            record(
+                &Log {
+                    lines: vec![1, 2, 3],
+                    level: 1
+                },
+                &Log {
+                    lines: vec![1, 3, 4],
+                    level: 1
+                }
+            ),
+            [
+                "enter_struct Log",
+                "field_changed lines",
+                "enter_sequence Vec<u32>",
+                "element_kept 1",
+                "element_removed 2",
+                "element_kept 3",
+                "element_added 4",
+                "exit_sequence Vec<u32>",
+                "field_unchanged level 1",
+                "exit_struct Log",
+            ]
+        );
+
+        assert_eq!(
+            record(
+                &Event::Started { at: 1 },
+                &Event::Stopped { at: 2, code: 0 }
+            ),
+            [
+                "enter_variant Started Stopped",
+                "field_changed at",
+                "leaf_replace 1 2",
+                "field_added code 0",
+                "exit_variant Started Stopped",
+            ]
+        );
+
+        assert!(record(&Event::Started { at: 1 }, &Event::Started { at: 1 }).is_empty());
+
+        // Fields are visited in declaration order, and entries in the order of their keys, whether
+        // they changed or not
+        #[derive(Facet)]
+        struct Wide {
+            a: u8,
+            b: u8,
+            c: u8,
+            d: u8,
+            e: u8,
+            f: u8,
+            g: u8,
+            h: u8,
+        }
+
+        let from = Wide {
+            a: 0,
+            b: 0,
+            c: 0,
+            d: 0,
+            e: 0,
+            f: 0,
+            g: 0,
+            h: 0,
+        };
+        let to = Wide {
+            a: 1,
+            b: 0,
+            c: 1,
+            d: 0,
+            e: 1,
+            f: 0,
+            g: 1,
+            h: 0,
+        };
+
+        let mut expected = vec!["enter_struct Wide".to_string()];
+        for (i, field) in ["a", "b", "c", "d", "e", "f", "g", "h"].iter().enumerate() {
+            if i % 2 == 0 {
+                expected.push(format!("field_changed {field}"));
+                expected.push("leaf_replace 0 1".into());
+            } else {
+                expected.push(format!("field_unchanged {field} 0"));
+            }
+        }
+        expected.push("exit_struct Wide".into());
+        assert_eq!(record(&from, &to), expected);
+
+        let from = std::collections::BTreeMap::from([("a", 0), ("b", 0), ("c", 0), ("d", 0)]);
+        let to = std::collections::BTreeMap::from([("a", 0), ("b", 1), ("d", 0), ("e", 0)]);
+        assert_eq!(
+            record(&from, &to),
+            [
+                "entry_unchanged a 0",
+                "entry_changed b",
+                "leaf_replace 0 1",
+                "entry_removed c 0",
+                "entry_unchanged d 0",
+                "entry_added e 0",
+            ]
+        );
+    }
+
+    #[test]
+    fn bytes_are_rendered_as_hex_dumps() {
+        let a = b"hello, world".to_vec();
+        let b = b"hello, World!".to_vec();
+
+        let rendered = a.diff(&b).to_string();
+        assert!(rendered.contains("00000000  68 65 6c 6c 6f 2c 20"));
+        assert!(rendered.contains("|hello, "));
+        assert!(rendered.contains("00000008  6f 72 6c 64"));
+        assert!(rendered.contains("\x1b[32m57\x1b[m"));
+
+        // An element whose bytes are not in the diff still takes its place in the dump, e.g. when a
+        // custom differ found the bytes equal
+        let a = *b"abcdefghijklmnop";
+        let b = *b"abcdefghiJklmnop";
+        let mut updates = positional::Positional::default();
+        for (i, byte) in a.iter().enumerate() {
+            match i {
+                3 => updates.updates.push((i, Diff::Equal)),
+                9 => updates.updates.push((
+                    i,
+                    Diff::Replace {
+                        from: Peek::new(byte),
+                        to: Peek::new(&b[i]),
+                    },
+                )),
+                _ => updates.unchanged.push((i, Peek::new(byte))),
+            }
+        }
+        let diff = Diff::Array {
+            from: <[u8; 16]>::SHAPE,
+            to: <[u8; 16]>::SHAPE,
+            updates,
+        };
+
+        let rendered = diff.to_string();
+        assert!(rendered.contains("00000000  61 62 63 .. 65 66 67 68  |abc.efgh|"));
+        assert!(rendered.contains("00000008  69 \x1b[31m6a\x1b[m 6b"));
+        assert!(rendered.contains("00000008  69 \x1b[32m4a\x1b[m 6b"));
+    }
+
+    #[derive(Facet)]
+    struct Graph {
+        left: std::rc::Rc<Point>,
+        right: std::rc::Rc<Point>,
+    }
+
+    #[test]
+    fn shared_subtrees_are_compared_once() {
+        let a = std::rc::Rc::new(Point {
+            x: 1,
+            y: 2,
+            tags: vec![],
+        });
+        let b = std::rc::Rc::new(Point {
+            x: 1,
+            y: 3,
+            tags: vec![],
+        });
+
+        let from = Graph {
+            left: a.clone(),
+            right: a,
+        };
+        let to = Graph {
+            left: b.clone(),
+            right: b,
+        };
+
+        // The shared value counts as a change at each path
+        let stats = from.diff(&to).stats();
+        assert_eq!((stats.changed, stats.fields, stats.depth), (2, 3, 2));
+
+        let OwnedDiff::User {
+            value: OwnedValue::Struct { updates, .. },
+            ..
+        } = OwnedDiff::from(&from.diff(&to))
+        else {
+            panic!("expected a struct diff");
+        };
+        assert!(matches!(updates["left"], OwnedDiff::User { .. }));
+        assert!(matches!(
+            updates["right"],
+            OwnedDiff::BackReference { cycle: false, .. }
+        ));
+    }
+
+    #[derive(Facet)]
+    #[repr(C)]
+    #[facet(untagged)]
+    #[allow(dead_code)]
+    enum Document {
+        Number(f64),
+        String(String),
+        Object(std::collections::BTreeMap<String, Document>),
+    }
+
+    #[derive(Facet)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    #[test]
+    fn structs_are_diffed_against_maps() {
+        let server = Server {
+            host: "localhost".into(),
+            port: 8080,
+        };
+        let document = Document::Object(
+            [
+                ("host".to_string(), Document::String("example.com".into())),
+                ("port".to_string(), Document::Number(8080.0)),
+                ("tls".to_string(), Document::Number(1.0)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let OwnedDiff::Map {
+            updates,
+            insertions,
+            unchanged,
+            ..
+        } = OwnedDiff::from(&server.diff(&document))
+        else {
+            panic!("expected a map diff");
+        };
+        assert_eq!(updates.keys().collect::<Vec<_>>(), ["host"]);
+        assert_eq!(insertions.keys().collect::<Vec<_>>(), ["tls"]);
+        assert_eq!(unchanged, ["port"]);
+
+        let a = std::collections::BTreeMap::from([("a", 1), ("b", 2)]);
+        let b = std::collections::BTreeMap::from([("b", 2), ("c", 3)]);
+        let stats = a.diff(&b).stats();
+        assert_eq!((stats.added, stats.removed, stats.unchanged), (1, 1, 1));
+        assert!(a.diff(&a).is_equal());
+    }
+
+    #[test]
+    fn unified_shows_context_around_changes() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: (0..10).collect(),
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: (1..10).collect(),
+        };
+
+        assert_eq!(
+            a.diff(&b).unified().context(1).colors(false).to_string(),
+            "  x: 1\n- y: 2\n+ y: 3\n- tags[0]: 0\n  tags[1]: 1\n@@ 8 unchanged @@\n"
+        );
+    }
+
+    #[test]
+    fn differ_options_are_applied_everywhere() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2, 3],
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: vec![2, 3, 4],
+        };
+
+        let differ = Differ::new().ignore("y").ignore("tags[*]");
+        assert!(differ.diff(&a, &b).unwrap().is_equal());
+
+        let differ = Differ::new().sequence_algorithm(SequenceAlgorithm::Positional);
+        let Diff::User {
+            value: Value::Struct { updates, .. },
+            ..
+        } = differ.diff(&a, &b).unwrap()
+        else {
+            panic!("expected a struct diff");
+        };
+        assert!(matches!(updates["tags"], Diff::Array { .. }));
+
+        let differ = Differ::new().max_depth(1);
+        let stats = differ.diff(&a, &b).unwrap().stats();
+        assert_eq!((stats.changed, stats.unchanged), (2, 1));
+
+        let differ = Differ::new().compare(|a: &Vec<u8>, b: &Vec<u8>| a.len() == b.len());
+        assert_eq!(differ.diff(&a, &b).unwrap().stats().changed, 1);
+
+        assert!(!1.0.diff(&1.05).is_equal());
+        let differ = Differ::new().tolerance(0.1);
+        assert!(differ.diff(&1.0, &1.05).unwrap().is_equal());
+    }
+
+    #[test]
+    fn ignored_paths_apply_to_shared_values_and_sequences() {
+        let a = std::rc::Rc::new(Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2],
+        });
+        let b = std::rc::Rc::new(Point {
+            x: 1,
+            y: 3,
+            tags: vec![1, 2, 3],
+        });
+
+        let from = Graph {
+            left: a.clone(),
+            right: a,
+        };
+        let to = Graph {
+            left: b.clone(),
+            right: b,
+        };
+
+        // The shared values are compared at each path, rather than once for both
+        let differ = Differ::new().ignore("left.y").ignore("**.tags");
+        let diff = differ.diff(&from, &to).unwrap();
+        assert_eq!(diff.compact().to_string(), "right.y: 2→3");
+
+        let differ = Differ::new().ignore("y").ignore("tags[*]");
+        assert!(differ.diff(&*from.left, &*to.left).unwrap().is_equal());
+
+        let differ = differ.sequence_algorithm(SequenceAlgorithm::Positional);
+        assert!(differ.diff(&*from.left, &*to.left).unwrap().is_equal());
+    }
+
+    #[test]
+    fn ignored_elements_keep_the_indices_of_the_others() {
+        let point = |tags| Point { x: 1, y: 2, tags };
+        let changes = |diff: &Diff| {
+            let Diff::User {
+                value: Value::Struct { updates, .. },
+                ..
+            } = diff
+            else {
+                panic!("expected a struct diff");
+            };
+            let Diff::Sequence { updates, .. } = &updates["tags"] else {
+                panic!("expected a sequence diff");
+            };
+
+            updates
+                .changes()
+                .into_iter()
+                .map(|change| match change {
+                    ElementChange::Kept { from, to, .. } => format!("{from}={to}"),
+                    ElementChange::Removed { from, .. } => format!("-{from}"),
+                    ElementChange::Added { to, .. } => format!("+{to}"),
+                    ElementChange::Replaced { from, to, .. } => format!("{from}~{to}"),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The ignored removal stays in place
+        let (a, b) = (point(vec![1, 2, 3, 4]), point(vec![2, 3, 5]));
+        let diff = Differ::new().ignore("tags[0]").diff(&a, &b).unwrap();
+        assert_eq!(changes(&diff), ["0=0", "1=1", "2=2", "-3", "+3"]);
+        assert_eq!(diff.compact().to_string(), "tags[3]: 4→5");
+
+        let (a, b) = (point(vec![1, 2, 3, 4]), point(vec![1, 3, 4]));
+        let diff = Differ::new().ignore("tags[1]").diff(&a, &b).unwrap();
+        assert!(diff.is_equal());
+
+        let (a, b) = (point(vec![1, 2, 3, 4]), point(vec![1, 3, 4, 9]));
+        let diff = Differ::new().ignore("tags[1]").diff(&a, &b).unwrap();
+        assert_eq!(changes(&diff), ["0=0", "1=1", "2=2", "3=3", "+4"]);
+        assert!(
+            diff.unified()
+                .colors(false)
+                .to_string()
+                .ends_with("  tags[3]: 4\n+ tags[4]: 9\n")
+        );
+
+        // The ignored addition is left out
+        let (a, b) = (point(vec![1, 2]), point(vec![1, 2, 7, 8]));
+        let diff = Differ::new().ignore("tags[2]").diff(&a, &b).unwrap();
+        assert_eq!(changes(&diff), ["0=0", "1=1", "+2"]);
+        assert_eq!(diff.compact().to_string(), "tags[+2]: 8");
+    }
+
+    #[test]
+    fn integers_are_compared_exactly() {
+        assert!(!9007199254740993u64.diff(&9007199254740992u64).is_equal());
+        assert!(!i64::MAX.diff(&(i64::MAX - 1)).is_equal());
+        assert!(!9007199254740993u64.diff(&9007199254740992i64).is_equal());
+        assert!(!u64::MAX.diff(&-1i64).is_equal());
+
+        assert!(9007199254740993u64.diff(&9007199254740993i64).is_equal());
+        assert!(2u8.diff(&2.0f64).is_equal());
+        assert!(!2u8.diff(&2.5f64).is_equal());
+
+        let differ = Differ::new().tolerance(1.0);
+        assert!(differ.diff(&i64::MAX, &(i64::MAX - 1)).unwrap().is_equal());
+        assert!(!differ.diff(&i64::MAX, &(i64::MAX - 2)).unwrap().is_equal());
+    }
+
+    #[test]
+    fn registered_types_replace_the_structural_diff() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2, 3],
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: vec![1, 2, 3],
+        };
+
+        let differ = Differ::new().registry(Registry::new().opaque::<Point>());
+        assert!(matches!(differ.diff(&a, &b).unwrap(), Diff::Replace { .. }));
+
+        let registry = Registry::new().compare_by_key(|point: &Point| point.tags.clone());
+        let differ = Differ::new().registry(registry);
+        assert!(differ.diff(&a, &b).unwrap().is_equal());
+
+        let registry = Registry::new().differ(<Vec<u8> as Facet>::SHAPE, |from, to| {
+            Diff::Inaccessible { from, to }
+        });
+        let differ = Differ::new().registry(registry);
+        let Diff::User {
+            value: Value::Struct { unchanged, .. },
+            ..
+        } = differ.diff(&a, &b).unwrap()
+        else {
+            panic!("expected a struct diff");
+        };
+        assert!(!unchanged.contains_key("tags"));
+    }
+
+    #[test]
+    fn sensitive_fields_are_redacted() {
+        #[derive(Facet)]
+        struct Login {
+            user: String,
+            #[facet(sensitive)]
+            password: String,
+            #[facet(sensitive)]
+            token: String,
+        }
+
+        let a = Login {
+            user: "ann".into(),
+            password: "hunter2".into(),
+            token: "abc".into(),
+        };
+        let b = Login {
+            user: "bob".into(),
+            password: "correct horse".into(),
+            token: "abc".into(),
+        };
+
+        let diff = a.diff(&b);
+        let rendered = format!("{diff}\n{}", diff.unified().colors(false));
+        assert!(rendered.contains("<redacted>"));
+        assert!(rendered.contains("\x1b[32m<redacted (changed)>"));
+        for secret in ["hunter2", "correct horse", "abc"] {
+            assert!(!rendered.contains(secret));
+        }
+        assert_eq!(diff.stats().changed, 2);
+
+        let diff = Differ::new().reveal_sensitive(true).diff(&a, &b).unwrap();
+        assert!(diff.unified().to_string().contains("hunter2"));
+
+        // Past the maximum depth, values that only differ in a sensitive field are still changed
+        #[derive(Facet)]
+        struct Session {
+            login: Login,
+        }
+
+        let a = Session { login: a };
+        let b = Session {
+            login: Login {
+                user: "ann".into(),
+                password: "correct horse".into(),
+                token: "abc".into(),
+            },
+        };
+        let diff = Differ::new().max_depth(1).diff(&a, &b).unwrap();
+        assert!(!diff.is_equal());
+        assert!(!diff.unified().to_string().contains("correct horse"));
+
+        // Opaque types without `PartialEq` are not compared by their redacted form either
+        let differ = Differ::new().registry(Registry::new().opaque::<Login>());
+        assert!(matches!(
+            differ.diff(&a.login, &b.login).unwrap(),
+            Diff::Replace { .. }
+        ));
+        let same = Login {
+            user: "ann".into(),
+            password: "hunter2".into(),
+            token: "abc".into(),
+        };
+        assert!(differ.diff(&a.login, &same).unwrap().is_equal());
+    }
+
+    #[test]
+    fn sensitive_tuple_fields_and_entries_are_redacted() {
+        #[derive(Facet)]
+        struct Token(#[facet(sensitive)] String, u32);
+
+        #[derive(Facet)]
+        #[repr(u8)]
+        #[allow(dead_code)]
+        enum Auth {
+            Bearer(#[facet(sensitive)] String, u32),
+            Anonymous,
+        }
+
+        #[derive(Facet)]
+        struct Login {
+            user: String,
+            #[facet(sensitive)]
+            password: String,
+        }
+
+        let render = |diff: &Diff| {
+            format!(
+                "{diff}\n{diff:#}\n{}\n{}",
+                diff.unified().colors(false),
+                diff.html()
+            )
+        };
+
+        let (a, b, c) = (
+            Token("hunter2".into(), 1),
+            Token("swordfish".into(), 1),
+            Token("hunter2".into(), 2),
+        );
+        let rendered = render(&a.diff(&b));
+        assert!(rendered.contains("<redacted (changed)>"));
+        assert!(!rendered.contains("hunter2") && !rendered.contains("swordfish"));
+        assert!(!render(&a.diff(&c)).contains("hunter2"));
+
+        let a = Auth::Bearer("hunter2".into(), 1);
+        let b = Auth::Bearer("swordfish".into(), 1);
+        let rendered = render(&a.diff(&b));
+        assert!(!rendered.contains("hunter2") && !rendered.contains("swordfish"));
+
+        // Unchanged sensitive fields compared against the entries of a map
+        let a = Login {
+            user: "ann".into(),
+            password: "hunter2".into(),
+        };
+        let b = std::collections::BTreeMap::from([("user", "bob"), ("password", "hunter2")]);
+        for diff in [a.diff(&b), b.diff(&a)] {
+            let rendered = render(&diff);
+            assert!(rendered.contains("password: <redacted>"));
+            assert!(!rendered.contains("hunter2"));
+        }
+    }
+
+    #[test]
+    fn html_collapses_unchanged_items() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2, 3],
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: vec![1, 2, 3, 4],
+        };
+
+        let html = a.diff(&b).html().stylesheet(false).to_string();
+        assert!(html.starts_with("<div class=\"facet-diff\">"));
+        assert!(html.contains(
+            "<li class=\"changed\"><span class=\"path\">y</span>: <del class=\"removed\"><code>2</code></del> → <ins class=\"added\"><code>3</code></ins></li>"
+        ));
+        assert!(html.contains("<li class=\"unchanged\"><details><summary>3 unchanged</summary>"));
+        assert!(html.contains("<li class=\"added\"><span class=\"path\">[3]</span>"));
+        assert!(html.contains("<summary>Vec&lt;u8&gt;</summary>"));
+
+        assert!(
+            Point {
+                x: 1,
+                y: 2,
+                tags: vec![]
+            }
+            .diff(&Point {
+                x: 1,
+                y: 2,
+                tags: vec![]
+            })
+            .html()
+            .to_string()
+            .contains("<style>")
+        );
+    }
+
+    #[test]
+    fn markdown_lists_changed_paths() {
+        let a = Point {
+            x: 1,
+            y: 2,
+            tags: vec![1, 2],
+        };
+        let b = Point {
+            x: 1,
+            y: 3,
+            tags: vec![1, 2, 3],
+        };
+
+        assert_eq!(
+            a.diff(&b).markdown().to_string(),
+            "| Path | Old | New |\n\
+             | --- | --- | --- |\n\
+             | `y` | `2` | `3` |\n\
+             | `tags[2]` | — | `3` |\n"
+        );
+        assert_eq!(a.diff(&a).markdown().to_string(), "No changes.\n");
+    }
+
+    #[test]
+    fn compact_fits_on_one_line() {
+        #[derive(Facet)]
+        struct User {
+            name: String,
+            age: u8,
+            tags: Vec<String>,
+        }
+
+        let a = User {
+            name: "ann".into(),
+            age: 30,
+            tags: vec!["a".into(), "b".into()],
+        };
+        let b = User {
+            name: "ann".into(),
+            age: 31,
+            tags: vec!["a".into(), "b".into(), "x".into()],
+        };
+
+        let diff = a.diff(&b);
+        assert_eq!(format!("{diff:#}"), r#"age: 30→31, tags[+2]: "x""#);
+        assert_eq!(diff.compact().max_len(12).to_string(), "age: 30→31,…");
+        assert_eq!(a.diff(&a).compact().to_string(), "equal");
+    }
+
+    #[test]
+    fn sequence_changes_are_labeled_with_indices() {
+        let a = vec![(0, "a"), (1, "b"), (2, "c")];
+        let b = vec![(1, "b"), (2, "x"), (3, "d")];
+
+        let diff = a.diff(&b);
+        let Diff::Sequence { updates, .. } = &diff else {
+            panic!("expected a sequence diff");
+        };
+
+        let changes = updates
+            .changes()
+            .into_iter()
+            .map(|change| match change {
+                ElementChange::Kept { from, to, .. } => format!("{from}={to}"),
+                ElementChange::Removed { from, .. } => format!("-{from}"),
+                ElementChange::Added { to, .. } => format!("+{to}"),
+                ElementChange::Replaced { from, to, .. } => format!("{from}~{to}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(changes, ["-0", "1=0", "2~1", "+2"]);
+
+        let tree = diff.to_string();
+        assert!(tree.contains("\x1b[31m[0] - "));
+        assert!(tree.contains("[2→1] ~ "));
+        assert!(tree.contains("\x1b[32m[2] + "));
+
+        let unified = diff.unified().colors(false).to_string();
+        assert!(unified.contains("- [2→1].1: c\n+ [2→1].1: x\n"));
+    }
+
+    #[test]
+    fn sequences_of_different_lengths_are_diffed() {
+        fn changes<'a, T: Facet<'a>>(a: &T, b: &T) -> Vec<String> {
+            let Diff::Sequence { updates, .. } = a.diff(b) else {
+                panic!("expected a sequence diff");
+            };
+
+            updates
+                .changes()
+                .into_iter()
+                .map(|change| match change {
+                    ElementChange::Kept { from, to, .. } => format!("{from}={to}"),
+                    ElementChange::Removed { from, .. } => format!("-{from}"),
+                    ElementChange::Added { to, .. } => format!("+{to}"),
+                    ElementChange::Replaced { from, to, .. } => format!("{from}~{to}"),
+                })
+                .collect()
+        }
+
+        // The edit distance table starts from the cost of removing or adding every element
+        assert_eq!(changes(&vec![1], &vec![2, 3, 4]), ["-0", "+0", "+1", "+2"]);
+        assert_eq!(changes(&vec![7, 1, 2], &vec![1, 2]), ["-0", "1=0", "2=1"]);
+
+        // A removed element is paired with the closest of more added elements, and the other way
+        // around
+        let point = |x, y| Point { x, y, tags: vec![] };
+        let a = vec![point(1, 1)];
+        let b = vec![point(5, 5), point(6, 6), point(1, 2)];
+        assert_eq!(changes(&a, &b), ["+0", "+1", "0~2"]);
+        assert_eq!(changes(&b, &a), ["-0", "-1", "2~0"]);
+    }
+
+    #[test]
+    fn long_values_are_elided() {
+        #[derive(Facet)]
+        struct Blob {
+            text: String,
+            lines: Option<Vec<u32>>,
+        }
+
+        let a = Blob {
+            text: format!("{}a{}", "x".repeat(1000), "y".repeat(1000)),
+            lines: None,
+        };
+        let b = Blob {
+            text: format!("{}b{}", "x".repeat(1000), "y".repeat(1000)),
+            lines: Some((0..100).collect()),
+        };
+
+        let diff = a.diff(&b);
+        let unified = diff
+            .unified()
+            .colors(false)
+            .max_value_len(10)
+            .max_lines(5)
+            .to_string();
+        assert!(unified.contains(
+            "- text: [… 995 characters …]xxxxxayyyy[… 996 characters …]\n\
+             + text: [… 995 characters …]xxxxxbyyyy[… 996 characters …]\n"
+        ));
+
+        let tree = diff.tree().max_value_len(10).max_lines(5).to_string();
+        assert!(tree.contains("[… 97 lines …]"));
+        assert!(tree.len() < 1000);
+    }
+
+    #[test]
+    fn include_restricts_the_diff() {
+        #[derive(Facet)]
+        struct Container {
+            name: String,
+            image: String,
+        }
+
+        #[derive(Facet)]
+        struct Spec {
+            replicas: u32,
+            containers: Vec<Container>,
+        }
+
+        #[derive(Facet)]
+        struct Deployment {
+            name: String,
+            spec: Spec,
+        }
+
+        let deployment = |name: &str, replicas, container: &str, image: &str| Deployment {
+            name: name.into(),
+            spec: Spec {
+                replicas,
+                containers: vec![Container {
+                    name: container.into(),
+                    image: image.into(),
+                }],
+            },
+        };
+
+        let a = deployment("web", 2, "nginx", "nginx:1.25");
+        let b = deployment("api", 3, "proxy", "nginx:1.25");
+        let differ = Differ::new().include("spec.containers[*].image");
+        assert!(differ.diff(&a, &b).unwrap().is_equal());
+
+        let c = deployment("api", 3, "proxy", "nginx:1.27");
+        let diff = differ.diff(&a, &c).unwrap();
+        assert_eq!(diff.stats().changed, 1);
+        assert_eq!(
+            diff.compact().to_string(),
+            r#"spec.containers[0].image: "nginx:1.25"→"nginx:1.27""#
+        );
+
+        let differ = Differ::new().include("spec").ignore("spec.replicas");
+        assert_eq!(differ.diff(&a, &b).unwrap().stats().changed, 1);
+    }
+
+    #[cfg(feature = "insta")]
+    #[test]
+    fn diffs_are_snapshotted() {
+        #[derive(Facet)]
+        struct Config {
+            name: String,
+            replicas: u32,
+            port: u16,
+        }
+
+        let a = Config {
+            name: "web".into(),
+            replicas: 2,
+            port: 80,
+        };
+        let b = Config {
+            name: "web".into(),
+            replicas: 3,
+            port: 80,
+        };
+
+        crate::assert_diff_snapshot!(a, a, @"equal");
+        crate::assert_diff_snapshot!(a, b, @r"
+          name: web
+        - replicas: 2
+        + replicas: 3
+          port: 80
+        ");
+
+        let panic = std::panic::catch_unwind(|| crate::assert_no_diff!(a, b)).unwrap_err();
+        assert_eq!(
+            panic.downcast_ref::<String>().unwrap(),
+            "the values differ:\n@@ 1 unchanged @@\n- replicas: 2\n+ replicas: 3\n@@ 1 unchanged @@\n"
+        );
+    }
 }
@@ -0,0 +1,132 @@
+use facet::{FieldFlags, StructKind};
+use facet_reflect::{HasFields, Peek};
+
+use crate::{Diff, context::Context, diff, path::Segment, pretty};
+
+/// The updates between two maps, or between a struct and a map, with entries matched by key.
+///
+/// Keys are stored as strings, struct fields are keyed by their name. All the lists are sorted by key.
+#[derive(Default)]
+pub struct Entries<'mem, 'facet> {
+    /// The entries that are in both values, but differ between them
+    pub updates: Vec<(String, Diff<'mem, 'facet>)>,
+
+    /// The entries that are in `from` but not in `to`
+    pub deletions: Vec<(String, Peek<'mem, 'facet>)>,
+
+    /// The entries that are in `to` but not in `from`
+    pub insertions: Vec<(String, Peek<'mem, 'facet>)>,
+
+    /// The entries that are equal in both values, along with their value in `from`
+    pub unchanged: Vec<(String, Peek<'mem, 'facet>)>,
+}
+
+impl<'mem, 'facet> Entries<'mem, 'facet> {
+    pub(crate) fn invert(self) -> Self {
+        Self {
+            updates: self
+                .updates
+                .into_iter()
+                .map(|(key, diff)| (key, diff.invert()))
+                .collect(),
+            deletions: self.insertions,
+            insertions: self.deletions,
+            unchanged: self.unchanged,
+        }
+    }
+}
+
+/// An entry of a map or a field of a struct, along with whether it is a sensitive field.
+type Entry<'mem, 'facet> = (String, Peek<'mem, 'facet>, bool);
+
+/// The entries of a map, or the fields of a struct with named fields, keyed by their string
+/// representation.
+pub(crate) fn entries<'mem, 'facet>(peek: Peek<'mem, 'facet>) -> Option<Vec<Entry<'mem, 'facet>>> {
+    if let Ok(map) = peek.into_map() {
+        return Some(
+            map.iter()
+                .map(|(key, value)| {
+                    let key = match diff::as_str(key) {
+                        Some(key) => key.to_string(),
+                        None => pretty::format(key),
+                    };
+                    (key, value, false)
+                })
+                .collect(),
+        );
+    }
+
+    let peek = peek.into_struct().ok()?;
+    if peek.ty().kind != StructKind::Struct {
+        return None;
+    }
+
+    Some(
+        peek.fields()
+            .map(|(field, value)| {
+                let sensitive = field.flags.contains(FieldFlags::SENSITIVE);
+                (field.name.to_string(), value, sensitive)
+            })
+            .collect(),
+    )
+}
+
+/// Compares two sets of entries by key.
+pub(crate) fn diff<'mem, 'facet>(
+    cx: &mut Context,
+    mut from: Vec<Entry<'mem, 'facet>>,
+    mut to: Vec<Entry<'mem, 'facet>>,
+) -> Entries<'mem, 'facet> {
+    from.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+    to.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    let mut entries = Entries::default();
+
+    let ignored = |cx: &mut Context, key: &str| {
+        cx.nested(Segment::Key(key.to_string()), |cx| cx.is_ignored())
+    };
+
+    let reveal = cx.options.reveal_sensitive;
+
+    for (key, from, sensitive) in &from {
+        match to.binary_search_by(|(x, ..)| x.cmp(key)) {
+            Ok(index) => {
+                let (_, to, to_sensitive) = to[index];
+                let diff = cx.nested(Segment::Key(key.clone()), |cx| {
+                    Diff::new_peek(cx, *from, to)
+                });
+                if diff.is_equal() {
+                    entries.unchanged.push((key.clone(), *from));
+                } else if !reveal && (*sensitive || to_sensitive) {
+                    entries
+                        .updates
+                        .push((key.clone(), Diff::redacted(*from, to)));
+                } else {
+                    entries.updates.push((key.clone(), diff));
+                }
+            }
+            Err(_) if ignored(cx, key) => {}
+            Err(_) if *sensitive && !reveal => entries
+                .updates
+                .push((key.clone(), Diff::redacted(*from, *from))),
+            Err(_) => entries.deletions.push((key.clone(), *from)),
+        }
+    }
+
+    for (key, to, sensitive) in to {
+        if from.binary_search_by(|(x, ..)| x.cmp(&key)).is_ok() || ignored(cx, &key) {
+            continue;
+        }
+
+        if sensitive && !reveal {
+            entries.updates.push((key, Diff::redacted(to, to)));
+        } else {
+            entries.insertions.push((key, to));
+        }
+    }
+
+    // Sensitive fields that are only in `to` are reported as updates
+    entries.updates.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    entries
+}
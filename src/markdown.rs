@@ -0,0 +1,152 @@
+use std::fmt::Display;
+
+use crate::{
+    Diff,
+    elide::Limits,
+    unified::{self, Kind},
+};
+
+/// A rendering of a [`Diff`] as GitHub-flavored Markdown, e.g. for a comment on a pull request.
+///
+/// It starts with a table of the changed paths with their old and new values. Values that span
+/// several lines, such as whole structs that were added, are left out of the table and shown in a
+/// fenced `diff` block below it, e.g.
+///
+/// ````markdown
+/// | Path | Old | New |
+/// | --- | --- | --- |
+/// | `replicas` | `2` | `3` |
+/// | `image` | *see below* | *see below* |
+///
+/// `image`
+/// ```diff
+/// - Image {
+/// -   tag: 1.25,
+/// - }
+/// + Image {
+/// +   tag: 1.27,
+/// + }
+/// ```
+/// ````
+pub struct Markdown<'a, 'mem, 'facet> {
+    diff: &'a Diff<'mem, 'facet>,
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Renders this diff as Markdown, cf. [`Markdown`].
+    pub fn markdown(&self) -> Markdown<'_, 'mem, 'facet> {
+        Markdown { diff: self }
+    }
+}
+
+/// The old and new values at a changed path.
+#[derive(Default)]
+struct Change {
+    path: String,
+    old: Option<String>,
+    new: Option<String>,
+}
+
+impl Change {
+    fn is_complex(&self) -> bool {
+        [&self.old, &self.new]
+            .into_iter()
+            .flatten()
+            .any(|text| text.contains('\n'))
+    }
+
+    fn longest_backticks(&self) -> usize {
+        [&self.old, &self.new]
+            .into_iter()
+            .flatten()
+            .flat_map(|text| text.split(|c| c != '`'))
+            .map(str::len)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Formats a value as inline code in a table cell.
+fn cell(text: Option<&str>) -> String {
+    let Some(text) = text else {
+        return "—".to_string();
+    };
+
+    if text.contains('\n') {
+        return "*see below*".to_string();
+    }
+
+    // Pipes end the cell even inside code, and backticks need a longer fence
+    let text = text.replace('|', "\\|");
+    match text.contains('`') {
+        true => format!("`` {text} ``"),
+        false => format!("`{text}`"),
+    }
+}
+
+impl<'a, 'mem, 'facet> Display for Markdown<'a, 'mem, 'facet> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut changes: Vec<Change> = Vec::new();
+
+        for item in unified::items(self.diff, Limits::default()) {
+            if item.kind == Kind::Unchanged {
+                continue;
+            }
+
+            if changes.last().is_none_or(|change| change.path != item.path) {
+                changes.push(Change {
+                    path: item.path.clone(),
+                    ..Default::default()
+                });
+            }
+
+            let Some(change) = changes.last_mut() else {
+                continue;
+            };
+            match item.kind {
+                Kind::Removed => change.old = Some(item.text),
+                Kind::Added | Kind::Note => change.new = Some(item.text),
+                Kind::Unchanged => {}
+            }
+        }
+
+        if changes.is_empty() {
+            return f.write_str("No changes.\n");
+        }
+
+        f.write_str("| Path | Old | New |\n| --- | --- | --- |\n")?;
+        for change in &changes {
+            let path = match change.path.as_str() {
+                "" => "*root*".to_string(),
+                path => cell(Some(path)),
+            };
+
+            writeln!(
+                f,
+                "| {path} | {} | {} |",
+                cell(change.old.as_deref()),
+                cell(change.new.as_deref())
+            )?;
+        }
+
+        for change in changes.iter().filter(|change| change.is_complex()) {
+            f.write_str("\n")?;
+            if !change.path.is_empty() {
+                writeln!(f, "`{}`", change.path)?;
+            }
+
+            // The fence has to be longer than any run of backticks in the values
+            let fence = "`".repeat(3.max(change.longest_backticks() + 1));
+
+            writeln!(f, "{fence}diff")?;
+            for (prefix, text) in [('-', &change.old), ('+', &change.new)] {
+                for line in text.iter().flat_map(|text| text.split('\n')) {
+                    writeln!(f, "{prefix} {line}")?;
+                }
+            }
+            writeln!(f, "{fence}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use facet_core::Facet;
+use facet_reflect::Peek;
+
+use crate::{BorrowPolicy, Diff, DiffError, Registry, context::Context, path::Pattern};
+
+/// How the elements of lists and other sequences are matched up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SequenceAlgorithm {
+    /// Find the smallest set of insertions and removals, then pair up similar elements (the default)
+    #[default]
+    Myers,
+
+    /// Compare the elements index by index, like arrays, cf. [`Diff::Array`]
+    Positional,
+}
+
+/// The configuration of a diff, shared by all the comparisons that it is made of.
+#[derive(Clone, Default)]
+pub(crate) struct DiffOptions {
+    pub(crate) ignore: Vec<Pattern>,
+    pub(crate) include: Vec<Pattern>,
+    pub(crate) tolerance: f64,
+    pub(crate) sequences: SequenceAlgorithm,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) registry: Registry,
+    pub(crate) policy: BorrowPolicy,
+    pub(crate) reveal_sensitive: bool,
+}
+
+/// Computes diffs with a given configuration.
+///
+/// ```
+/// # use facet::Facet;
+/// # use facet_diff::Differ;
+/// #[derive(Facet)]
+/// struct Reading {
+///     value: f64,
+///     taken_at: u64,
+/// }
+///
+/// let a = Reading { value: 1.0, taken_at: 10 };
+/// let b = Reading { value: 1.0 + 1e-12, taken_at: 20 };
+///
+/// let differ = Differ::new().ignore("taken_at").tolerance(1e-9);
+/// assert!(differ.diff(&a, &b).unwrap().is_equal());
+/// ```
+#[derive(Clone, Default)]
+pub struct Differ {
+    options: DiffOptions,
+}
+
+impl Differ {
+    /// A differ with the default configuration, which is the one used by [`Diff::new`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Treats the values at the paths matching `pattern` as unchanged, without comparing them.
+    ///
+    /// Paths are made of field names and map keys separated by dots, and of indices in brackets,
+    /// e.g. `spec.containers[0].image`. `*` matches any single segment, `[*]` any element and `**`
+    /// any number of segments, e.g. `**.updated_at` ignores that field everywhere. Indices refer to
+    /// the positions of elements in the `from` value, except for added elements, which are matched
+    /// by their position in the `to` value.
+    ///
+    /// An ignored element that is only in the `from` sequence is kept in the diff, and one that is
+    /// only in the `to` sequence is left out. The indices of the other elements count them
+    /// accordingly, like a patch that leaves the ignored elements alone.
+    pub fn ignore(mut self, pattern: &str) -> Self {
+        self.options.ignore.push(Pattern::parse(pattern));
+        self
+    }
+
+    /// Only compares the values at the paths matching `pattern`, e.g. `spec.containers[*].image`,
+    /// along with everything inside them. All the other values are treated as unchanged, without
+    /// walking into them.
+    ///
+    /// Patterns are written as for [`Differ::ignore`], and this can be called several times to
+    /// include several paths. Ignored paths are left out of the included ones.
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.options.include.push(Pattern::parse(pattern));
+        self
+    }
+
+    /// Considers numbers equal when they differ by at most `tolerance`, 0 by default.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.options.tolerance = tolerance;
+        self
+    }
+
+    /// Sets how the elements of lists are matched up, cf. [`SequenceAlgorithm`].
+    pub fn sequence_algorithm(mut self, algorithm: SequenceAlgorithm) -> Self {
+        self.options.sequences = algorithm;
+        self
+    }
+
+    /// Stops walking into values that are nested more than `depth` fields, entries or elements
+    /// deep. Such values are compared as a whole, and reported as replaced if they differ.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.options.max_depth = Some(depth);
+        self
+    }
+
+    /// Compares the values of type `T` with `equal` rather than structurally, e.g. to compare URLs
+    /// by their normalized form. Unequal values are reported as replaced.
+    ///
+    /// This is a shorthand for [`Registry::compare`].
+    pub fn compare<T>(mut self, equal: impl Fn(&T, &T) -> bool + Send + Sync + 'static) -> Self
+    where
+        T: for<'a> Facet<'a>,
+    {
+        self.options.registry = self.options.registry.compare(equal);
+        self
+    }
+
+    /// Uses the custom comparison logic of `registry`, which takes precedence over the types
+    /// registered so far.
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.options.registry.extend(registry);
+        self
+    }
+
+    /// Sets what to do with values behind locks or cells that cannot be accessed, cf.
+    /// [`BorrowPolicy`].
+    pub fn borrow_policy(mut self, policy: BorrowPolicy) -> Self {
+        self.options.policy = policy;
+        self
+    }
+
+    /// Shows the values of sensitive fields (`#[facet(sensitive)]`) in the diff, e.g. for debugging
+    /// in a trusted environment.
+    ///
+    /// By default, changed sensitive fields are reported as [`Diff::Redacted`], without their values.
+    pub fn reveal_sensitive(mut self, reveal: bool) -> Self {
+        self.options.reveal_sensitive = reveal;
+        self
+    }
+
+    /// Computes the difference between two values.
+    ///
+    /// This only fails with [`BorrowPolicy::Error`].
+    pub fn diff<'mem, 'facet, T: Facet<'facet>, U: Facet<'facet>>(
+        &self,
+        from: &'mem T,
+        to: &'mem U,
+    ) -> Result<Diff<'mem, 'facet>, DiffError> {
+        self.diff_peek(Peek::new(from), Peek::new(to))
+    }
+
+    /// Computes the difference between two values that were already reflected.
+    pub fn diff_peek<'mem, 'facet>(
+        &self,
+        from: Peek<'mem, 'facet>,
+        to: Peek<'mem, 'facet>,
+    ) -> Result<Diff<'mem, 'facet>, DiffError> {
+        let mut cx = Context::new(&self.options);
+
+        let diff = Diff::new_peek(&mut cx, from, to);
+        match cx.error {
+            Some(error) => Err(error),
+            None => Ok(diff),
+        }
+    }
+}
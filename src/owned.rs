@@ -0,0 +1,505 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use facet::{Facet, Shape, TypeNameOpts};
+use facet_pretty::PrettyPrinter;
+use facet_reflect::Peek;
+
+use crate::{
+    diff::{Diff, Value},
+    positional::Positional,
+    sequences::{ReplaceGroup, Updates, UpdatesGroup},
+};
+
+/// An owned, serializable version of a [`Diff`].
+///
+/// Unlike [`Diff`], this does not borrow the values that were compared, so it can be stored or sent
+/// elsewhere. Since it implements [`Facet`] itself, it can be written and read back with any of the
+/// facet format crates, e.g. `facet-json` or `facet-msgpack`.
+///
+/// Values are stored as [`OwnedPeek`]s, i.e. their type name and their pretty-printed representation.
+#[derive(Facet, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum OwnedDiff {
+    /// The two values are equal
+    Equal,
+
+    /// Fallback case, the two values are unequal to each other.
+    Replace {
+        /// The `from` value.
+        from: OwnedPeek,
+
+        /// The `to` value.
+        to: OwnedPeek,
+    },
+
+    /// The two values are both structures or both enums with similar variants.
+    User {
+        /// The type name of the `from` value.
+        from: String,
+
+        /// The type name of the `to` value.
+        to: String,
+
+        /// The name of the variant of the `from` enum, this is [`None`] if the values are structs
+        from_variant: Option<String>,
+
+        /// The name of the variant of the `to` enum, this is [`None`] if the values are structs
+        to_variant: Option<String>,
+
+        /// cf. [`OwnedValue`]
+        value: OwnedValue,
+    },
+
+    /// A diff between two sequences
+    Sequence {
+        /// The type name of the `from` sequence.
+        from: String,
+
+        /// The type name of the `to` sequence.
+        to: String,
+
+        /// The updates on the sequence, in order
+        updates: Vec<OwnedUpdate>,
+    },
+
+    /// A diff between two arrays or slices, compared index by index
+    Array {
+        /// The type name of the `from` array.
+        from: String,
+
+        /// The type name of the `to` array.
+        to: String,
+
+        /// cf. [`OwnedPositional`]
+        updates: OwnedPositional,
+    },
+
+    /// A diff between two maps, or between a struct and a map
+    Map {
+        /// The type name of the `from` value.
+        from: String,
+
+        /// The type name of the `to` value.
+        to: String,
+
+        /// The entries that are updated between the values
+        updates: BTreeMap<String, OwnedDiff>,
+
+        /// The entries that are in `from` but not in `to`.
+        deletions: BTreeMap<String, OwnedPeek>,
+
+        /// The entries that are in `to` but not in `from`.
+        insertions: BTreeMap<String, OwnedPeek>,
+
+        /// The keys of the entries that are unchanged, sorted
+        unchanged: Vec<String>,
+    },
+
+    /// One of the values is `None` and the other is `Some`
+    Presence {
+        /// The type name of the `from` option.
+        from: String,
+
+        /// The type name of the `to` option.
+        to: String,
+
+        /// The value inside whichever option is `Some`
+        value: OwnedPeek,
+
+        /// Whether the value became present (`None` to `Some`), or absent (`Some` to `None`)
+        present: bool,
+    },
+
+    /// The values are behind pointers, and were already compared elsewhere in the diff
+    BackReference {
+        /// The type name of the `from` value.
+        from: String,
+
+        /// The type name of the `to` value.
+        to: String,
+
+        /// Whether the values are being compared further up, i.e. they contain themselves
+        cycle: bool,
+    },
+
+    /// One of the values is behind a lock or a cell that cannot be accessed
+    Inaccessible {
+        /// The type name of the `from` value.
+        from: String,
+
+        /// The type name of the `to` value.
+        to: String,
+    },
+
+    /// The values of a sensitive field differ, and were left out
+    Redacted {
+        /// The type name of the `from` value.
+        from: String,
+
+        /// The type name of the `to` value.
+        to: String,
+    },
+
+    /// One of the values is `Ok` and the other is `Err`
+    Outcome {
+        /// The type name of the `from` result.
+        from: String,
+
+        /// The type name of the `to` result.
+        to: String,
+
+        /// The value inside the `from` result
+        from_value: OwnedPeek,
+
+        /// The value inside the `to` result
+        to_value: OwnedPeek,
+
+        /// Whether the outcome went from `Err` to `Ok`, or from `Ok` to `Err`
+        ok: bool,
+    },
+}
+
+/// An owned, serializable version of the updates between two structs, tuples or enum variants.
+#[derive(Facet, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum OwnedValue {
+    /// The updates between two tuples
+    Tuple {
+        /// The updates on the fields, compared by position
+        updates: OwnedPositional,
+    },
+
+    /// The updates between two structs
+    Struct {
+        /// The fields that are updated between the structs
+        updates: BTreeMap<String, OwnedDiff>,
+
+        /// The fields that are in `from` but not in `to`.
+        deletions: BTreeMap<String, OwnedPeek>,
+
+        /// The fields that are in `to` but not in `from`.
+        insertions: BTreeMap<String, OwnedPeek>,
+
+        /// The fields that are unchanged, sorted by name
+        unchanged: Vec<String>,
+    },
+}
+
+/// An owned, serializable version of the updates between two sequences that are compared index by
+/// index. All the lists are sorted by index.
+#[derive(Facet, Debug, Clone, PartialEq)]
+pub struct OwnedPositional {
+    /// The elements that are in both sequences, but differ between them
+    pub updates: Vec<Indexed<OwnedDiff>>,
+
+    /// The elements past the end of the `to` sequence
+    pub deletions: Vec<Indexed<OwnedPeek>>,
+
+    /// The elements past the end of the `from` sequence
+    pub insertions: Vec<Indexed<OwnedPeek>>,
+
+    /// The indices of the elements that are unchanged
+    pub unchanged: Vec<u64>,
+}
+
+/// A value along with its index in a sequence.
+#[derive(Facet, Debug, Clone, PartialEq)]
+pub struct Indexed<T> {
+    /// The index in the sequence
+    pub index: u64,
+
+    /// The value at that index
+    pub value: T,
+}
+
+impl<T> Indexed<T> {
+    fn new(index: usize, value: impl Into<T>) -> Self {
+        Self {
+            index: index as u64,
+            value: value.into(),
+        }
+    }
+}
+
+/// A single step in the updates of a sequence.
+#[derive(Facet, Debug, Clone, PartialEq)]
+#[repr(C)]
+pub enum OwnedUpdate {
+    /// The element is in both sequences
+    Keep {
+        /// The element
+        value: OwnedPeek,
+    },
+
+    /// The element is only in the `from` sequence
+    Remove {
+        /// The element
+        value: OwnedPeek,
+    },
+
+    /// The element is only in the `to` sequence
+    Add {
+        /// The element
+        value: OwnedPeek,
+    },
+
+    /// The element was changed in place
+    Update {
+        /// The diff between the `from` and the `to` element
+        diff: OwnedDiff,
+    },
+}
+
+/// An owned representation of a single value.
+#[derive(Facet, Debug, Clone, PartialEq)]
+pub struct OwnedPeek {
+    /// The type name of the value
+    pub shape: String,
+
+    /// The pretty-printed value, without colors
+    pub value: String,
+}
+
+impl<'mem, 'facet> From<Peek<'mem, 'facet>> for OwnedPeek {
+    fn from(peek: Peek<'mem, 'facet>) -> Self {
+        Self {
+            shape: type_name(peek.shape()),
+            value: PrettyPrinter::default()
+                .with_colors(false)
+                .format_peek(peek),
+        }
+    }
+}
+
+impl<'mem, 'facet> From<&Diff<'mem, 'facet>> for OwnedDiff {
+    fn from(diff: &Diff<'mem, 'facet>) -> Self {
+        match diff {
+            Diff::Equal => Self::Equal,
+            Diff::Replace { from, to } => Self::Replace {
+                from: (*from).into(),
+                to: (*to).into(),
+            },
+            Diff::User {
+                from,
+                to,
+                from_variant,
+                to_variant,
+                value,
+            } => Self::User {
+                from: type_name(from),
+                to: type_name(to),
+                from_variant: from_variant.map(String::from),
+                to_variant: to_variant.map(String::from),
+                value: value.into(),
+            },
+            Diff::Sequence { from, to, updates } => Self::Sequence {
+                from: type_name(from),
+                to: type_name(to),
+                updates: updates.into(),
+            },
+            Diff::Array { from, to, updates } => Self::Array {
+                from: type_name(from),
+                to: type_name(to),
+                updates: updates.into(),
+            },
+            Diff::Map { from, to, updates } => Self::Map {
+                from: type_name(from),
+                to: type_name(to),
+                updates: updates
+                    .updates
+                    .iter()
+                    .map(|(key, diff)| (key.clone(), diff.into()))
+                    .collect(),
+                deletions: updates
+                    .deletions
+                    .iter()
+                    .map(|(key, value)| (key.clone(), (*value).into()))
+                    .collect(),
+                insertions: updates
+                    .insertions
+                    .iter()
+                    .map(|(key, value)| (key.clone(), (*value).into()))
+                    .collect(),
+                unchanged: updates
+                    .unchanged
+                    .iter()
+                    .map(|(key, _)| key.clone())
+                    .collect(),
+            },
+            Diff::Presence {
+                from,
+                to,
+                value,
+                present,
+            } => Self::Presence {
+                from: type_name(from),
+                to: type_name(to),
+                value: (*value).into(),
+                present: *present,
+            },
+            Diff::BackReference { from, to, cycle } => Self::BackReference {
+                from: type_name(from.shape()),
+                to: type_name(to.shape()),
+                cycle: *cycle,
+            },
+            Diff::Inaccessible { from, to } => Self::Inaccessible {
+                from: type_name(from.shape()),
+                to: type_name(to.shape()),
+            },
+            Diff::Redacted { from, to } => Self::Redacted {
+                from: type_name(from),
+                to: type_name(to),
+            },
+            Diff::Outcome {
+                from,
+                to,
+                from_value,
+                to_value,
+                ok,
+            } => Self::Outcome {
+                from: type_name(from),
+                to: type_name(to),
+                from_value: (*from_value).into(),
+                to_value: (*to_value).into(),
+                ok: *ok,
+            },
+        }
+    }
+}
+
+impl<'mem, 'facet> From<&Value<'mem, 'facet>> for OwnedValue {
+    fn from(value: &Value<'mem, 'facet>) -> Self {
+        match value {
+            Value::Tuple { updates } => Self::Tuple {
+                updates: updates.into(),
+            },
+            Value::Struct {
+                updates,
+                deletions,
+                insertions,
+                unchanged,
+            } => {
+                let mut unchanged = unchanged
+                    .keys()
+                    .map(|field| field.to_string())
+                    .collect::<Vec<_>>();
+                unchanged.sort();
+
+                Self::Struct {
+                    updates: updates
+                        .iter()
+                        .map(|(field, diff)| (field.to_string(), diff.into()))
+                        .collect(),
+                    deletions: deletions
+                        .iter()
+                        .map(|(field, value)| (field.to_string(), (*value).into()))
+                        .collect(),
+                    insertions: insertions
+                        .iter()
+                        .map(|(field, value)| (field.to_string(), (*value).into()))
+                        .collect(),
+                    unchanged,
+                }
+            }
+        }
+    }
+}
+
+impl<'mem, 'facet> From<&Positional<'mem, 'facet>> for OwnedPositional {
+    fn from(positional: &Positional<'mem, 'facet>) -> Self {
+        Self {
+            updates: positional
+                .updates
+                .iter()
+                .map(|(index, diff)| Indexed::new(*index, diff))
+                .collect(),
+            deletions: positional
+                .deletions
+                .iter()
+                .map(|(index, value)| Indexed::new(*index, *value))
+                .collect(),
+            insertions: positional
+                .insertions
+                .iter()
+                .map(|(index, value)| Indexed::new(*index, *value))
+                .collect(),
+            unchanged: positional
+                .unchanged
+                .iter()
+                .map(|(index, _)| *index as u64)
+                .collect(),
+        }
+    }
+}
+
+impl<'mem, 'facet> From<&Updates<'mem, 'facet>> for Vec<OwnedUpdate> {
+    fn from(updates: &Updates<'mem, 'facet>) -> Self {
+        let mut owned = Vec::new();
+
+        let keep = |owned: &mut Vec<OwnedUpdate>, values: &Vec<Peek<'mem, 'facet>>| {
+            owned.extend(values.iter().map(|value| OwnedUpdate::Keep {
+                value: (*value).into(),
+            }))
+        };
+
+        if let Some(group) = &updates.0.first {
+            push_group(&mut owned, group);
+        }
+
+        for (values, group) in &updates.0.values {
+            keep(&mut owned, values);
+            push_group(&mut owned, group);
+        }
+
+        if let Some(values) = &updates.0.last {
+            keep(&mut owned, values);
+        }
+
+        owned
+    }
+}
+
+fn push_group(owned: &mut Vec<OwnedUpdate>, group: &UpdatesGroup) {
+    let diffs = |owned: &mut Vec<OwnedUpdate>, diffs: &Vec<Diff>| {
+        owned.extend(
+            diffs
+                .iter()
+                .map(|diff| OwnedUpdate::Update { diff: diff.into() }),
+        )
+    };
+
+    if let Some(replace) = &group.0.first {
+        push_replace(owned, replace);
+    }
+
+    for (values, replace) in &group.0.values {
+        diffs(owned, values);
+        push_replace(owned, replace);
+    }
+
+    if let Some(values) = &group.0.last {
+        diffs(owned, values);
+    }
+}
+
+fn push_replace(owned: &mut Vec<OwnedUpdate>, replace: &ReplaceGroup) {
+    owned.extend(replace.removals.iter().map(|value| OwnedUpdate::Remove {
+        value: (*value).into(),
+    }));
+    owned.extend(replace.additions.iter().map(|value| OwnedUpdate::Add {
+        value: (*value).into(),
+    }));
+}
+
+pub(crate) fn type_name(shape: &'static Shape) -> String {
+    struct TypeName(&'static Shape);
+
+    impl Display for TypeName {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            self.0.write_type_name(f, TypeNameOpts::infinite())
+        }
+    }
+
+    TypeName(shape).to_string()
+}
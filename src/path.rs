@@ -0,0 +1,132 @@
+/// A step from a value to one of its children.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Segment {
+    /// A field of a struct or an enum variant
+    Field(&'static str),
+
+    /// An entry of a map
+    Key(String),
+
+    /// An element of a sequence, or a field of a tuple, by its position in the `from` value
+    Index(usize),
+}
+
+/// A pattern matching the paths to values, such as `spec.containers[*].image`.
+///
+/// Segments are separated by dots, or written in brackets. `*` matches any single segment, `[*]`
+/// any element and `**` any number of segments, including none. Indices refer to the positions
+/// of elements in the `from` value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Pattern(Vec<Step>);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Name(String),
+    Index(usize),
+    AnyIndex,
+    Any,
+    AnyDepth,
+}
+
+impl Step {
+    fn matches(&self, segment: &Segment) -> bool {
+        match (self, segment) {
+            (Step::Any | Step::AnyDepth, _) => true,
+            (Step::Name(name), Segment::Field(field)) => name == field,
+            (Step::Name(name), Segment::Key(key)) => name == key,
+            // Fields of tuples are elements, and maps can have integer keys
+            (Step::Name(name), Segment::Index(index)) => *name == index.to_string(),
+            (Step::Index(index), Segment::Key(key)) => index.to_string() == *key,
+            (Step::Index(index), Segment::Index(other)) => index == other,
+            (Step::AnyIndex, Segment::Index(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Pattern {
+    /// Parses a pattern, any malformed bracket is taken as part of a name.
+    pub(crate) fn parse(pattern: &str) -> Self {
+        let mut steps = Vec::new();
+
+        for part in pattern.split('.').filter(|part| !part.is_empty()) {
+            let (name, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+            match name {
+                "" => {}
+                "*" => steps.push(Step::Any),
+                "**" => steps.push(Step::AnyDepth),
+                name => steps.push(Step::Name(name.to_string())),
+            }
+
+            while let Some((inner, tail)) = rest.strip_prefix('[').and_then(|x| x.split_once(']')) {
+                steps.push(match inner {
+                    "*" => Step::AnyIndex,
+                    inner => match inner.parse() {
+                        Ok(index) => Step::Index(index),
+                        Err(_) => Step::Name(inner.trim_matches('"').to_string()),
+                    },
+                });
+                rest = tail;
+            }
+
+            if !rest.is_empty() {
+                steps.push(Step::Name(rest.to_string()));
+            }
+        }
+
+        Pattern(steps)
+    }
+
+    /// Whether the pattern matches the whole path.
+    pub(crate) fn matches(&self, path: &[Segment]) -> bool {
+        matches(&self.0, path)
+    }
+
+    /// Whether the pattern matches the path or one of its ancestors, i.e. the path is in a subtree
+    /// selected by the pattern.
+    pub(crate) fn contains(&self, path: &[Segment]) -> bool {
+        contains(&self.0, path)
+    }
+
+    /// Whether the path is an ancestor of the paths that the pattern could match.
+    pub(crate) fn leads_to(&self, path: &[Segment]) -> bool {
+        leads_to(&self.0, path)
+    }
+}
+
+fn matches(steps: &[Step], path: &[Segment]) -> bool {
+    match (steps.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((Step::AnyDepth, rest)), _) => {
+            matches(rest, path) || (!path.is_empty() && matches(steps, &path[1..]))
+        }
+        (Some((step, rest)), Some((segment, path))) => step.matches(segment) && matches(rest, path),
+        _ => false,
+    }
+}
+
+fn contains(steps: &[Step], path: &[Segment]) -> bool {
+    match (steps.split_first(), path.split_first()) {
+        (None, _) => true,
+        (Some((Step::AnyDepth, rest)), _) => {
+            contains(rest, path) || (!path.is_empty() && contains(steps, &path[1..]))
+        }
+        (Some((step, rest)), Some((segment, path))) => {
+            step.matches(segment) && contains(rest, path)
+        }
+        (Some(_), None) => false,
+    }
+}
+
+fn leads_to(steps: &[Step], path: &[Segment]) -> bool {
+    match (steps.split_first(), path.split_first()) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        // Any path can be followed by the segments that `**` matches
+        (Some((Step::AnyDepth, _)), _) => true,
+        (Some((step, rest)), Some((segment, path))) => {
+            step.matches(segment) && leads_to(rest, path)
+        }
+    }
+}
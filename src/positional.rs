@@ -0,0 +1,136 @@
+use facet_reflect::Peek;
+
+use crate::{Diff, context::Context, path::Segment};
+
+/// The updates between two sequences that are compared index by index, e.g. two arrays.
+///
+/// If the sequences have different lengths, the elements past the end of the shorter one are
+/// deletions or insertions. Sensitive fields of tuple structs that are only in one of them are
+/// reported as updates instead, like the fields of structs. All the lists are sorted by index.
+#[derive(Default)]
+pub struct Positional<'mem, 'facet> {
+    /// The elements that are in both sequences, but differ between them
+    pub updates: Vec<(usize, Diff<'mem, 'facet>)>,
+
+    /// The elements past the end of the `to` sequence
+    pub deletions: Vec<(usize, Peek<'mem, 'facet>)>,
+
+    /// The elements past the end of the `from` sequence
+    pub insertions: Vec<(usize, Peek<'mem, 'facet>)>,
+
+    /// The elements that are equal in both sequences, along with their value in `from`
+    pub unchanged: Vec<(usize, Peek<'mem, 'facet>)>,
+
+    lengths: (usize, usize),
+}
+
+impl<'mem, 'facet> Positional<'mem, 'facet> {
+    /// The number of elements in the `from` and the `to` sequence
+    pub fn lengths(&self) -> (usize, usize) {
+        self.lengths
+    }
+
+    /// Whether the two sequences have different lengths
+    pub fn resized(&self) -> bool {
+        self.lengths.0 != self.lengths.1
+    }
+
+    /// Whether there are no updates, deletions or insertions, e.g. when they are all ignored
+    pub(crate) fn is_empty(&self) -> bool {
+        self.updates.is_empty() && self.deletions.is_empty() && self.insertions.is_empty()
+    }
+
+    /// The elements that are in both sequences, in order
+    pub(crate) fn common(&self) -> impl Iterator<Item = (usize, Common<'_, 'mem, 'facet>)> {
+        let mut updates = self.updates.iter().peekable();
+        let mut unchanged = self.unchanged.iter().peekable();
+
+        std::iter::from_fn(move || match (updates.peek(), unchanged.peek()) {
+            (Some((a, _)), Some((b, _))) if b < a => unchanged
+                .next()
+                .map(|(i, value)| (*i, Common::Unchanged(*value))),
+            (Some(_), _) => updates.next().map(|(i, diff)| (*i, Common::Changed(diff))),
+            (None, _) => unchanged
+                .next()
+                .map(|(i, value)| (*i, Common::Unchanged(*value))),
+        })
+    }
+
+    pub(crate) fn invert(self) -> Self {
+        Self {
+            updates: self
+                .updates
+                .into_iter()
+                .map(|(i, diff)| (i, diff.invert()))
+                .collect(),
+            deletions: self.insertions,
+            insertions: self.deletions,
+            unchanged: self.unchanged,
+            lengths: (self.lengths.1, self.lengths.0),
+        }
+    }
+}
+
+/// An element that is in both sequences, cf. [`Positional::common`]
+pub(crate) enum Common<'a, 'mem, 'facet> {
+    Changed(&'a Diff<'mem, 'facet>),
+    Unchanged(Peek<'mem, 'facet>),
+}
+
+/// Compares two sequences index by index.
+pub(crate) fn diff<'mem, 'facet>(
+    cx: &mut Context,
+    from: Vec<Peek<'mem, 'facet>>,
+    to: Vec<Peek<'mem, 'facet>>,
+) -> Positional<'mem, 'facet> {
+    let mut positional = Positional {
+        lengths: (from.len(), to.len()),
+        ..Default::default()
+    };
+
+    for (i, (from, to)) in from.iter().zip(&to).enumerate() {
+        let diff = cx.nested(Segment::Index(i), |cx| Diff::new_peek(cx, *from, *to));
+        if diff.is_equal() {
+            positional.unchanged.push((i, *from));
+        } else {
+            positional.updates.push((i, diff));
+        }
+    }
+
+    let ignored = |cx: &mut Context, i| cx.nested(Segment::Index(i), |cx| cx.is_ignored());
+
+    for (i, from) in from.iter().enumerate().skip(to.len()) {
+        if !ignored(cx, i) {
+            positional.deletions.push((i, *from));
+        }
+    }
+
+    for (i, to) in to.iter().enumerate().skip(from.len()) {
+        if !ignored(cx, i) {
+            positional.insertions.push((i, *to));
+        }
+    }
+
+    positional
+}
+
+/// Compares the single value inside two wrappers, e.g. two `Some`s, which do not appear in paths.
+pub(crate) fn single<'mem, 'facet>(
+    cx: &mut Context,
+    from: Peek<'mem, 'facet>,
+    to: Peek<'mem, 'facet>,
+) -> Positional<'mem, 'facet> {
+    let mut positional = Positional {
+        lengths: (1, 1),
+        ..Default::default()
+    };
+
+    let diff = Diff::new_peek(cx, from, to);
+    if diff.is_equal() {
+        positional.unchanged.push((0, from));
+    } else {
+        positional.updates.push((0, diff));
+    }
+
+    positional
+}
@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use facet::{Def, Shape, ShapeAttribute};
+use facet_pretty::PrettyPrinter;
+use facet_reflect::Peek;
+
+use crate::diff;
+
+/// Formats a value for a rendering of a diff, without colors.
+///
+/// Untagged enums (`#[facet(untagged)]`) are formatted as the value inside their variant, e.g. a
+/// dynamic document shows `[a, b]` rather than `Value::Array(Vec<Value> [Value::String(a), ...])`.
+pub(crate) fn format(peek: Peek) -> String {
+    let mut out = String::new();
+    let _ = write(&mut out, peek);
+    out
+}
+
+/// How `facet-pretty` prints the value of a sensitive field (`#[facet(sensitive)]`).
+const REDACTED: &str = "[REDACTED]";
+
+/// Whether two values are formatted the same, for values that cannot be compared with `PartialEq`.
+///
+/// Values with sensitive fields are never the same, since those fields are redacted when formatted
+/// and could differ.
+pub(crate) fn same_text(from: Peek, to: Peek) -> bool {
+    let from = format(from);
+    !from.contains(REDACTED) && from == format(to)
+}
+
+fn is_untagged(shape: &'static Shape) -> bool {
+    shape
+        .attributes
+        .contains(&ShapeAttribute::Arbitrary("untagged"))
+}
+
+fn write(out: &mut String, mut peek: Peek) -> std::fmt::Result {
+    while let Some(inner) = diff::untagged(peek) {
+        peek = inner;
+    }
+
+    let shape = peek.shape();
+
+    if is_untagged(shape) {
+        // A variant without a value, e.g. `Null`
+        if let Some(variant) = peek
+            .into_enum()
+            .ok()
+            .and_then(|peek| peek.variant_name_active().ok())
+        {
+            return out.write_str(variant);
+        }
+    }
+
+    match shape.def {
+        Def::List(def) if is_untagged(def.t()) => {
+            let list = peek.into_list_like().map_err(|_| std::fmt::Error)?;
+            write_block(out, '[', ']', list.iter().map(|value| (None, value)))
+        }
+        Def::Map(def) if is_untagged(def.v()) => {
+            let map = peek.into_map().map_err(|_| std::fmt::Error)?;
+            write_block(
+                out,
+                '{',
+                '}',
+                map.iter().map(|(key, value)| (Some(key), value)),
+            )
+        }
+        _ => out.write_str(
+            &PrettyPrinter::default()
+                .with_colors(false)
+                .format_peek(peek),
+        ),
+    }
+}
+
+/// Writes the values of a list or a map, one per line and indented.
+fn write_block<'mem, 'facet>(
+    out: &mut String,
+    open: char,
+    close: char,
+    values: impl Iterator<Item = (Option<Peek<'mem, 'facet>>, Peek<'mem, 'facet>)>,
+) -> std::fmt::Result {
+    let mut values = values.peekable();
+    if values.peek().is_none() {
+        return write!(out, "{open}{close}");
+    }
+
+    writeln!(out, "{open}")?;
+
+    for (key, value) in values {
+        out.push_str("  ");
+
+        if let Some(key) = key {
+            match diff::as_str(key) {
+                Some(key) => out.push_str(key),
+                None => write(out, key)?,
+            }
+            out.push_str(": ");
+        }
+
+        let value = format(value);
+        for (i, line) in value.split('\n').enumerate() {
+            if i > 0 {
+                out.push_str("\n  ");
+            }
+            out.push_str(line);
+        }
+
+        out.push_str(",\n");
+    }
+
+    out.write_char(close)
+}
@@ -0,0 +1,162 @@
+use std::{any::TypeId, collections::HashMap, sync::Arc};
+
+use facet::Shape;
+use facet_core::Facet;
+use facet_reflect::Peek;
+
+use crate::{Diff, context::Context, options::DiffOptions};
+
+/// A custom differ, cf. [`Registry::differ`].
+type CustomDiffer = Arc<
+    dyn for<'mem, 'facet> Fn(Peek<'mem, 'facet>, Peek<'mem, 'facet>) -> Diff<'mem, 'facet>
+        + Send
+        + Sync,
+>;
+
+/// How the values of a registered type are compared.
+#[derive(Clone)]
+enum Custom {
+    Compare(Arc<dyn Fn(Peek, Peek) -> bool + Send + Sync>),
+    Opaque,
+    Differ(CustomDiffer),
+}
+
+/// Custom comparison logic for specific types, which replaces the structural diff for their values.
+///
+/// This is useful for third-party types whose `PartialEq` is missing or does not fit, or whose
+/// reflected structure is not worth showing. Types are registered by type or by [`Shape`], and the
+/// registry is installed with [`Differ::registry`](crate::Differ::registry).
+///
+/// ```
+/// # use facet::Facet;
+/// # use facet_diff::{Differ, Registry};
+/// #[derive(Facet)]
+/// struct Decimal {
+///     mantissa: i64,
+///     scale: u32,
+/// }
+///
+/// impl Decimal {
+///     fn value(&self) -> f64 {
+///         self.mantissa as f64 / 10f64.powi(self.scale as i32)
+///     }
+/// }
+///
+/// let registry = Registry::new().compare(|a: &Decimal, b: &Decimal| a.value() == b.value());
+/// let differ = Differ::new().registry(registry);
+///
+/// let a = Decimal { mantissa: 150, scale: 2 };
+/// let b = Decimal { mantissa: 15, scale: 1 };
+/// assert!(differ.diff(&a, &b).unwrap().is_equal());
+/// ```
+#[derive(Clone, Default)]
+pub struct Registry {
+    types: HashMap<TypeId, Custom>,
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares the values of type `T` with `equal`. Unequal values are reported as replaced.
+    pub fn compare<T>(self, equal: impl Fn(&T, &T) -> bool + Send + Sync + 'static) -> Self
+    where
+        T: for<'a> Facet<'a>,
+    {
+        self.compare_shape(<T as Facet>::SHAPE, move |from, to| {
+            match (from.get::<T>(), to.get::<T>()) {
+                (Ok(from), Ok(to)) => equal(from, to),
+                _ => false,
+            }
+        })
+    }
+
+    /// Compares the values of type `T` by a key, e.g. a URL by its normalized form.
+    pub fn compare_by_key<T, K: PartialEq>(
+        self,
+        key: impl Fn(&T) -> K + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: for<'a> Facet<'a>,
+    {
+        self.compare(move |from: &T, to: &T| key(from) == key(to))
+    }
+
+    /// Compares the values of this shape with `equal`. Unequal values are reported as replaced.
+    pub fn compare_shape(
+        mut self,
+        shape: &'static Shape,
+        equal: impl Fn(Peek, Peek) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.types
+            .insert(shape.id.get(), Custom::Compare(Arc::new(equal)));
+        self
+    }
+
+    /// Treats the values of type `T` as scalars, which are either equal or replaced, without
+    /// walking into them.
+    pub fn opaque<T>(self) -> Self
+    where
+        T: for<'a> Facet<'a>,
+    {
+        self.opaque_shape(<T as Facet>::SHAPE)
+    }
+
+    /// Treats the values of this shape as scalars, cf. [`Registry::opaque`].
+    ///
+    /// They are compared with `PartialEq` if the shape has it, and structurally with the default
+    /// options otherwise.
+    pub fn opaque_shape(mut self, shape: &'static Shape) -> Self {
+        self.types.insert(shape.id.get(), Custom::Opaque);
+        self
+    }
+
+    /// Diffs the values of this shape with `differ`, which can return any kind of [`Diff`].
+    pub fn differ(
+        mut self,
+        shape: &'static Shape,
+        differ: impl for<'mem, 'facet> Fn(Peek<'mem, 'facet>, Peek<'mem, 'facet>) -> Diff<'mem, 'facet>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        self.types
+            .insert(shape.id.get(), Custom::Differ(Arc::new(differ)));
+        self
+    }
+
+    /// Adds all the types of `other`, which take precedence over the ones already registered.
+    pub(crate) fn extend(&mut self, other: Registry) {
+        self.types.extend(other.types);
+    }
+
+    /// Diffs two values with the custom logic registered for their type, if any.
+    pub(crate) fn diff<'mem, 'facet>(
+        &self,
+        from: Peek<'mem, 'facet>,
+        to: Peek<'mem, 'facet>,
+    ) -> Option<Diff<'mem, 'facet>> {
+        if from.shape().id != to.shape().id {
+            return None;
+        }
+
+        let equal = match self.types.get(&from.shape().id.get())? {
+            Custom::Compare(equal) => equal(from, to),
+            Custom::Opaque if from.shape().is_partial_eq() => from == to,
+            // Compared structurally, without this registry, since their formatted forms hide the
+            // sensitive fields
+            Custom::Opaque => {
+                let options = DiffOptions::default();
+                Diff::new_peek(&mut Context::new(&options), from, to).is_equal()
+            }
+            Custom::Differ(differ) => return Some(differ(from, to)),
+        };
+
+        Some(match equal {
+            true => Diff::Equal,
+            false => Diff::Replace { from, to },
+        })
+    }
+}
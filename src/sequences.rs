@@ -1,6 +1,6 @@
 use facet_reflect::Peek;
 
-use crate::Diff;
+use crate::{Diff, context::Context, path::Segment};
 
 pub(crate) struct Interspersed<A, B> {
     pub(crate) first: Option<A>,
@@ -32,6 +32,16 @@ impl<A, B> Interspersed<A, B> {
     }
 }
 
+impl<A, B> Interspersed<A, B> {
+    fn map<C, D>(self, mut f: impl FnMut(A) -> C, mut g: impl FnMut(B) -> D) -> Interspersed<C, D> {
+        Interspersed {
+            first: self.first.map(&mut f),
+            values: self.values.into_iter().map(|(b, a)| (g(b), f(a))).collect(),
+            last: self.last.map(g),
+        }
+    }
+}
+
 impl<A, B> Default for Interspersed<A, B> {
     fn default() -> Self {
         Self {
@@ -60,6 +70,52 @@ impl<'mem, 'facet> ReplaceGroup<'mem, 'facet> {
     fn push_remove(&mut self, removal: Peek<'mem, 'facet>) {
         self.removals.insert(0, removal);
     }
+
+    fn invert(self) -> Self {
+        Self {
+            removals: self.additions,
+            additions: self.removals,
+        }
+    }
+
+    fn changes<'a>(
+        &'a self,
+        changes: &mut Vec<ElementChange<'a, 'mem, 'facet>>,
+        cursor: &mut Cursor,
+    ) {
+        for value in &self.removals {
+            changes.push(ElementChange::Removed {
+                from: cursor.from,
+                value: *value,
+            });
+            cursor.from += 1;
+        }
+
+        for value in &self.additions {
+            changes.push(ElementChange::Added {
+                to: cursor.to,
+                value: *value,
+            });
+            cursor.to += 1;
+        }
+    }
+}
+
+/// The index of the next element in each sequence, cf. [`Updates::changes`].
+#[derive(Default)]
+struct Cursor {
+    from: usize,
+    to: usize,
+}
+
+impl Cursor {
+    /// Moves past an element that is in both sequences, returning its indices.
+    fn both(&mut self) -> (usize, usize) {
+        let indices = (self.from, self.to);
+        self.from += 1;
+        self.to += 1;
+        indices
+    }
 }
 
 #[derive(Default)]
@@ -76,50 +132,162 @@ impl<'mem, 'facet> UpdatesGroup<'mem, 'facet> {
         self.0.front_a().push_remove(removal);
     }
 
-    fn flatten(&mut self) {
+    fn changes<'a>(
+        &'a self,
+        changes: &mut Vec<ElementChange<'a, 'mem, 'facet>>,
+        cursor: &mut Cursor,
+    ) {
+        let replaced =
+            |changes: &mut Vec<_>, cursor: &mut Cursor, diffs: &'a [Diff<'mem, 'facet>]| {
+                for diff in diffs {
+                    let (from, to) = cursor.both();
+                    changes.push(ElementChange::Replaced { from, to, diff });
+                }
+            };
+
+        if let Some(group) = &self.0.first {
+            group.changes(changes, cursor);
+        }
+
+        for (diffs, group) in &self.0.values {
+            replaced(changes, cursor, diffs);
+            group.changes(changes, cursor);
+        }
+
+        if let Some(diffs) = &self.0.last {
+            replaced(changes, cursor, diffs);
+        }
+    }
+
+    fn invert(self) -> Self {
+        Self(self.0.map(ReplaceGroup::invert, |diffs| {
+            diffs.into_iter().map(Diff::invert).collect()
+        }))
+    }
+
+    /// Pairs up the removals and additions, the first removal being at index `start` in the `from`
+    /// sequence. Returns the number of removals.
+    fn flatten(&mut self, cx: &mut Context, start: usize) -> usize {
         let Some(updates) = self.0.first.take() else {
-            return;
+            return 0;
         };
 
-        let mut mem = vec![vec![0; updates.removals.len() + 1]];
+        let mut mem = vec![vec![0; updates.additions.len() + 1]];
 
-        for x in 0..updates.removals.len() {
+        for (x, removal) in updates.removals.iter().enumerate() {
             let mut row = vec![0];
 
-            for y in 0..updates.additions.len() {
-                row.push(row.last().copied().unwrap().max(
-                    mem[x][y]
-                        + Diff::new_peek(updates.removals[x], updates.additions[y]).closeness(),
-                ));
+            for (y, addition) in updates.additions.iter().enumerate() {
+                let closeness = cx.nested(Segment::Index(start + x), |cx| {
+                    cx.scratch(|cx| Diff::new_peek(cx, *removal, *addition).closeness())
+                });
+
+                row.push(row.last().copied().unwrap().max(mem[x][y] + closeness));
             }
 
             mem.push(row);
         }
 
+        // Backtrack to find which removals and additions are paired up, back to front
+        let mut steps = Vec::new();
         let mut x = updates.removals.len();
         let mut y = updates.additions.len();
 
         while x > 0 || y > 0 {
             if x == 0 {
-                self.push_add(updates.additions[y - 1]);
+                steps.push(Step::Add(y - 1));
                 y -= 1;
             } else if y == 0 {
-                self.push_remove(updates.removals[x - 1]);
+                steps.push(Step::Remove(x - 1));
                 x -= 1;
             } else if mem[x][y - 1] == mem[x][y] {
-                self.push_add(updates.additions[y - 1]);
+                steps.push(Step::Add(y - 1));
                 y -= 1;
             } else {
-                let diff = Diff::new_peek(updates.removals[x - 1], updates.additions[y - 1]);
-                self.0.front_b().insert(0, diff);
-
+                steps.push(Step::Pair(x - 1, y - 1));
                 x -= 1;
                 y -= 1;
             }
         }
+
+        // Diff the pairs front to back, so that shared values are compared at their first occurrence
+        let mut diffs = steps
+            .iter()
+            .rev()
+            .filter_map(|step| match step {
+                Step::Pair(x, y) => Some(cx.nested(Segment::Index(start + x), |cx| {
+                    Diff::new_peek(cx, updates.removals[*x], updates.additions[*y])
+                })),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for step in steps {
+            match step {
+                Step::Add(y) => self.push_add(updates.additions[y]),
+                Step::Remove(x) => self.push_remove(updates.removals[x]),
+                Step::Pair(..) => self.0.front_b().insert(0, diffs.pop().unwrap()),
+            }
+        }
+
+        updates.removals.len()
     }
 }
 
+/// A step of the backtracking in [`UpdatesGroup::flatten`]
+enum Step {
+    Add(usize),
+    Remove(usize),
+    Pair(usize, usize),
+}
+
+/// An element of a diff between two sequences, along with its position in them, cf.
+/// [`Updates::changes`].
+pub enum ElementChange<'a, 'mem, 'facet> {
+    /// The element is in both sequences
+    Kept {
+        /// The index of the element in the `from` sequence
+        from: usize,
+
+        /// The index of the element in the `to` sequence
+        to: usize,
+
+        /// The element
+        value: Peek<'mem, 'facet>,
+    },
+
+    /// The element is only in the `from` sequence
+    Removed {
+        /// The index of the element in the `from` sequence
+        from: usize,
+
+        /// The element
+        value: Peek<'mem, 'facet>,
+    },
+
+    /// The element is only in the `to` sequence
+    Added {
+        /// The index of the element in the `to` sequence
+        to: usize,
+
+        /// The element
+        value: Peek<'mem, 'facet>,
+    },
+
+    /// The element was changed in place
+    Replaced {
+        /// The index of the element in the `from` sequence
+        from: usize,
+
+        /// The index of the element in the `to` sequence
+        to: usize,
+
+        /// The diff between the `from` and the `to` element
+        diff: &'a Diff<'mem, 'facet>,
+    },
+}
+
+/// The updates between two sequences, cf. [`Diff::Sequence`].
 #[derive(Default)]
 pub struct Updates<'mem, 'facet>(
     pub(crate) Interspersed<UpdatesGroup<'mem, 'facet>, Vec<Peek<'mem, 'facet>>>,
@@ -136,9 +304,50 @@ impl<'mem, 'facet> Updates<'mem, 'facet> {
         self.0.front_a().push_remove(removal);
     }
 
-    pub(crate) fn closeness(&self) -> usize {
-        self.0.values.iter().map(|(x, _)| x.len()).sum::<usize>()
-            + self.0.last.as_ref().map(|x| x.len()).unwrap_or_default()
+    /// Swaps the additions and removals, so that the updates go from the `to` sequence to the `from` sequence.
+    pub(crate) fn invert(self) -> Self {
+        Self(self.0.map(UpdatesGroup::invert, |values| values))
+    }
+
+    /// Whether all the elements were kept.
+    pub(crate) fn is_unchanged(&self) -> bool {
+        self.0.first.is_none() && self.0.values.is_empty()
+    }
+
+    /// Lists every element of the two sequences in order, with its index in each of them.
+    ///
+    /// Removed elements come before the elements added in their place. When some elements are
+    /// ignored, cf. [`Differ::ignore`](crate::Differ::ignore), the indices in the `to` sequence
+    /// are those it has once the ignored elements are left as they were in `from`.
+    pub fn changes(&self) -> Vec<ElementChange<'_, 'mem, 'facet>> {
+        let mut changes = Vec::new();
+        let mut cursor = Cursor::default();
+
+        let kept = |changes: &mut Vec<_>, cursor: &mut Cursor, values: &[Peek<'mem, 'facet>]| {
+            for value in values {
+                let (from, to) = cursor.both();
+                changes.push(ElementChange::Kept {
+                    from,
+                    to,
+                    value: *value,
+                });
+            }
+        };
+
+        if let Some(group) = &self.0.first {
+            group.changes(&mut changes, &mut cursor);
+        }
+
+        for (values, group) in &self.0.values {
+            kept(&mut changes, &mut cursor, values);
+            group.changes(&mut changes, &mut cursor);
+        }
+
+        if let Some(values) = &self.0.last {
+            kept(&mut changes, &mut cursor, values);
+        }
+
+        changes
     }
 
     /// All `push_*` methods on [`Updates`] push from the front, because the myers' algorithm finds updates back to front.
@@ -146,19 +355,24 @@ impl<'mem, 'facet> Updates<'mem, 'facet> {
         self.0.front_b().insert(0, value);
     }
 
-    fn flatten(&mut self) {
+    fn flatten(&mut self, cx: &mut Context) {
+        // The index of the next element in the `from` sequence
+        let mut index = 0;
+
         if let Some(update) = &mut self.0.first {
-            update.flatten()
+            index += update.flatten(cx, index);
         }
 
-        for (_, update) in &mut self.0.values {
-            update.flatten()
+        for (values, update) in &mut self.0.values {
+            index += values.len();
+            index += update.flatten(cx, index);
         }
     }
 }
 
 /// Gets the diff of a sequence by using myers' algorithm
-pub fn diff<'mem, 'facet>(
+pub(crate) fn diff<'mem, 'facet>(
+    cx: &mut Context,
     a: Vec<Peek<'mem, 'facet>>,
     b: Vec<Peek<'mem, 'facet>>,
 ) -> Updates<'mem, 'facet> {
@@ -168,13 +382,36 @@ pub fn diff<'mem, 'facet>(
     // Moving diagonally does both, which has no effect and thus has no cost
     // This can only be done when the items are the same
     //
-    let mut mem = vec![vec![0; a.len() + 1]];
+    // Ignored elements are never removed or added, so moving past them has no cost either. An
+    // ignored removal stays in place as a kept element, so that the indices of the elements after it
+    // still follow the `from` sequence, and an ignored addition is left out, like in positional diffs.
+    let ignored = |cx: &mut Context, len: usize| {
+        (0..len)
+            .map(|i| cx.nested(Segment::Index(i), |cx| cx.is_ignored()))
+            .collect::<Vec<_>>()
+    };
+    let (ignored_a, ignored_b) = (ignored(cx, a.len()), ignored(cx, b.len()));
+    let remove_cost = |x: usize| !ignored_a[x] as usize;
+    let add_cost = |y: usize| !ignored_b[y] as usize;
+
+    let equal = |cx: &mut Context, x: usize, y: usize| {
+        !ignored_a[x]
+            && cx.nested(Segment::Index(x), |cx| {
+                cx.scratch(|cx| Diff::new_peek(cx, a[x], b[y]).is_equal())
+            })
+    };
+
+    let mut first = vec![0];
+    for x in 0..a.len() {
+        first.push(first[x] + remove_cost(x));
+    }
+    let mut mem = vec![first];
 
     for y in 0..b.len() {
-        let mut next = vec![0];
+        let mut next = vec![mem[y][0] + add_cost(y)];
         for x in 0..a.len() {
-            let mut v = mem[y][x + 1].min(next[x]) + 1;
-            if Diff::new_peek(a[x], b[y]).is_equal() {
+            let mut v = (mem[y][x + 1] + add_cost(y)).min(next[x] + remove_cost(x));
+            if equal(cx, x, y) {
                 v = v.min(mem[y][x]);
             }
 
@@ -186,30 +423,46 @@ pub fn diff<'mem, 'facet>(
 
     let mut updates = Updates::default();
 
+    let remove = |updates: &mut Updates<'mem, 'facet>, x: usize| {
+        if ignored_a[x] {
+            updates.push_keep(a[x]);
+        } else {
+            updates.push_remove(a[x]);
+        }
+    };
+    let add = |updates: &mut Updates<'mem, 'facet>, y: usize| {
+        if !ignored_b[y] {
+            updates.push_add(b[y]);
+        }
+    };
+
     let mut x = a.len();
     let mut y = b.len();
     while x > 0 || y > 0 {
         if y == 0 {
-            updates.push_remove(a[x - 1]);
+            remove(&mut updates, x - 1);
             x -= 1;
         } else if x == 0 {
-            updates.push_add(b[y - 1]);
-            y -= 1;
-        } else if Diff::new_peek(a[x - 1], b[y - 1]).is_equal()
-            && mem[y - 1][x - 1] <= mem[y][x - 1].min(mem[y - 1][x]) + 1
-        {
-            updates.push_keep(a[x - 1]);
-            x -= 1;
+            add(&mut updates, y - 1);
             y -= 1;
-        } else if mem[y][x - 1] < mem[y - 1][x] {
-            updates.push_remove(a[x - 1]);
-            x -= 1;
         } else {
-            updates.push_add(b[y - 1]);
-            y -= 1;
+            let removal = mem[y][x - 1] + remove_cost(x - 1);
+            let addition = mem[y - 1][x] + add_cost(y - 1);
+
+            if equal(cx, x - 1, y - 1) && mem[y - 1][x - 1] <= removal.min(addition) {
+                updates.push_keep(a[x - 1]);
+                x -= 1;
+                y -= 1;
+            } else if removal < addition {
+                remove(&mut updates, x - 1);
+                x -= 1;
+            } else {
+                add(&mut updates, y - 1);
+                y -= 1;
+            }
         }
     }
 
-    updates.flatten();
+    updates.flatten(cx);
     updates
 }
@@ -0,0 +1,175 @@
+use facet::{Def, Shape, Type, UserType};
+use facet_core::Facet;
+use facet_reflect::{HasFields, Peek};
+
+use crate::{Diff, DiffVisitor};
+
+/// Computes how similar two values are, from 0 (nothing in common) to 1 (equal).
+///
+/// Every value is weighted by the number of leaves it contains, so a large subtree that is equal in
+/// both values counts for more than a single matching field. This is useful to find the closest
+/// match for a value among a number of candidates.
+pub fn similarity<'a, 'f, T: Facet<'f>, U: Facet<'f>>(from: &'a T, to: &'a U) -> f64 {
+    Diff::new(from, to).similarity()
+}
+
+/// The number of leaves that two values have in common, and the number of leaves in each of them.
+#[derive(Default)]
+pub(crate) struct Weights {
+    pub(crate) common: usize,
+    pub(crate) from: usize,
+    pub(crate) to: usize,
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Computes how similar the two values were, from 0 (nothing in common) to 1 (equal).
+    ///
+    /// cf. [`similarity`]
+    pub fn similarity(&self) -> f64 {
+        if self.is_equal() {
+            return 1.0;
+        }
+
+        // Values without any leaves, e.g. empty structs of different types, have nothing that differs
+        let weights = self.weights();
+        match weights.from + weights.to {
+            0 => 1.0,
+            total => 2.0 * weights.common as f64 / total as f64,
+        }
+    }
+
+    pub(crate) fn weights(&self) -> Weights {
+        if self.is_equal() {
+            // We do not know the values, but this never happens for the nested diffs that we are
+            // interested in, since those are never equal.
+            return Weights {
+                common: 1,
+                from: 1,
+                to: 1,
+            };
+        }
+
+        let mut weights = Weights::default();
+        self.walk(&mut weights);
+        weights
+    }
+}
+
+impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for Weights {
+    fn field_unchanged(&mut self, _field: &'static str, value: Peek<'mem, 'facet>) {
+        let leaves = leaves(value);
+        self.common += leaves;
+        self.from += leaves;
+        self.to += leaves;
+    }
+
+    fn field_added(&mut self, _field: &'static str, value: Peek<'mem, 'facet>) {
+        self.to += leaves(value);
+    }
+
+    fn field_removed(&mut self, _field: &'static str, value: Peek<'mem, 'facet>) {
+        self.from += leaves(value);
+    }
+
+    fn entry_unchanged(&mut self, _key: &str, value: Peek<'mem, 'facet>) {
+        let leaves = leaves(value);
+        self.common += leaves;
+        self.from += leaves;
+        self.to += leaves;
+    }
+
+    fn entry_added(&mut self, _key: &str, value: Peek<'mem, 'facet>) {
+        self.to += leaves(value);
+    }
+
+    fn entry_removed(&mut self, _key: &str, value: Peek<'mem, 'facet>) {
+        self.from += leaves(value);
+    }
+
+    fn element_kept(&mut self, value: Peek<'mem, 'facet>) {
+        let leaves = leaves(value);
+        self.common += leaves;
+        self.from += leaves;
+        self.to += leaves;
+    }
+
+    fn element_added(&mut self, value: Peek<'mem, 'facet>) {
+        self.to += leaves(value);
+    }
+
+    fn element_removed(&mut self, value: Peek<'mem, 'facet>) {
+        self.from += leaves(value);
+    }
+
+    fn became_present(&mut self, value: Peek<'mem, 'facet>) {
+        self.from += 1;
+        self.to += leaves(value);
+    }
+
+    fn became_absent(&mut self, value: Peek<'mem, 'facet>) {
+        self.from += leaves(value);
+        self.to += 1;
+    }
+
+    fn back_reference(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>, _cycle: bool) {
+        self.from += 1;
+        self.to += 1;
+    }
+
+    fn inaccessible(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>) {
+        self.from += 1;
+        self.to += 1;
+    }
+
+    fn redacted(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.from += 1;
+        self.to += 1;
+    }
+
+    fn outcome_flipped(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>, _ok: bool) {
+        self.from += leaves(from);
+        self.to += leaves(to);
+    }
+
+    fn leaf_replace(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {
+        self.from += leaves(from);
+        self.to += leaves(to);
+    }
+}
+
+/// Counts the leaves in a value, every value counts as at least one leaf.
+pub(crate) fn leaves(peek: Peek) -> usize {
+    let leaves = match (peek.shape().def, peek.shape().ty) {
+        (Def::Option(_), _) => peek
+            .into_option()
+            .unwrap()
+            .value()
+            .map(leaves)
+            .unwrap_or_default(),
+        (Def::List(_) | Def::Array(_) | Def::Slice(_), _) | (_, Type::Sequence(_)) => peek
+            .into_list_like()
+            .map(|list| list.iter().map(leaves).sum())
+            .unwrap_or_default(),
+        (Def::Map(_), _) => peek
+            .into_map()
+            .unwrap()
+            .iter()
+            .map(|(_, value)| leaves(value))
+            .sum(),
+        (_, Type::User(UserType::Struct(_))) => peek
+            .into_struct()
+            .unwrap()
+            .fields()
+            .map(|(_, value)| leaves(value))
+            .sum(),
+        (_, Type::User(UserType::Enum(_))) => peek
+            .into_enum()
+            .unwrap()
+            .fields()
+            .map(|(_, value)| leaves(value))
+            .sum(),
+        _ => 1,
+    };
+
+    leaves.max(1)
+}
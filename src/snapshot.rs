@@ -0,0 +1,80 @@
+//! Snapshot testing with [`insta`], which is enabled by the `insta` feature.
+//!
+//! Rather than snapshotting a `Debug` dump of a large value,
+//! [`assert_diff_snapshot!`](crate::assert_diff_snapshot) snapshots its diff against a baseline
+//! value, so that a change shows up as a readable structural diff:
+//!
+//! ```
+//! # use facet::Facet;
+//! # use facet_diff::assert_diff_snapshot;
+//! #[derive(Facet)]
+//! struct Config {
+//!     name: String,
+//!     replicas: u32,
+//! }
+//!
+//! let baseline = Config { name: "web".into(), replicas: 2 };
+//! let current = Config { name: "web".into(), replicas: 3 };
+//!
+//! assert_diff_snapshot!(baseline, current, @r"
+//!   name: web
+//! - replicas: 2
+//! + replicas: 3
+//! ");
+//! ```
+
+use facet_core::Facet;
+
+use crate::Diff;
+
+#[doc(hidden)]
+pub use insta as __insta;
+
+/// Renders the diff between two values as plain text, as it is stored in snapshots.
+///
+/// This is the unified rendering of the diff without colors, or `equal` if there is no difference.
+pub fn render<'f, T: Facet<'f>, U: Facet<'f>>(baseline: &T, current: &U) -> String {
+    let diff = Diff::new(baseline, current);
+
+    match diff.is_equal() {
+        true => "equal\n".to_string(),
+        false => diff.unified().colors(false).to_string(),
+    }
+}
+
+/// Asserts that the diff between a baseline value and the current one matches a snapshot.
+///
+/// This takes the same forms as [`insta::assert_snapshot!`]: with a snapshot name, without one, or
+/// with an inline snapshot after `@`.
+#[macro_export]
+macro_rules! assert_diff_snapshot {
+    ($baseline:expr, $current:expr, @$snapshot:literal $(,)?) => {
+        $crate::snapshot::__insta::assert_snapshot!(
+            $crate::snapshot::render(&$baseline, &$current),
+            @$snapshot
+        )
+    };
+    ($name:expr, $baseline:expr, $current:expr $(,)?) => {
+        $crate::snapshot::__insta::assert_snapshot!(
+            $name,
+            $crate::snapshot::render(&$baseline, &$current)
+        )
+    };
+    ($baseline:expr, $current:expr $(,)?) => {
+        $crate::snapshot::__insta::assert_snapshot!($crate::snapshot::render(&$baseline, &$current))
+    };
+}
+
+/// Asserts that two values are equal, and panics with their structural diff otherwise.
+#[macro_export]
+macro_rules! assert_no_diff {
+    ($baseline:expr, $current:expr $(,)?) => {{
+        let diff = $crate::Diff::new(&$baseline, &$current);
+        if !diff.is_equal() {
+            panic!(
+                "the values differ:\n{}",
+                diff.unified().colors(false).context(0)
+            );
+        }
+    }};
+}
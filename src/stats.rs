@@ -0,0 +1,193 @@
+use std::fmt::Display;
+
+use facet::Shape;
+use facet_reflect::Peek;
+
+use crate::{Diff, DiffVisitor, diff};
+
+/// Summary statistics of a [`Diff`], cf. [`Diff::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DiffStats {
+    /// The number of leaves that were replaced by another value, including shared values that
+    /// changed and values that could not be accessed
+    pub changed: usize,
+
+    /// The number of fields and elements that are only in the `to` value
+    pub added: usize,
+
+    /// The number of fields and elements that are only in the `from` value
+    pub removed: usize,
+
+    /// The number of fields and elements that are equal in both values
+    pub unchanged: usize,
+
+    /// The number of fields and map entries, at any depth, that were changed, added or removed
+    pub fields: usize,
+
+    /// The number of sequence elements, at any depth, that were changed, added or removed
+    pub elements: usize,
+
+    /// The maximum depth of a change, where a change to the values themselves has depth 0 and a
+    /// change to one of their fields or elements has depth 1
+    pub depth: usize,
+
+    /// How similar the two values are, from 0 (nothing in common) to 1 (equal), cf. [`Diff::similarity`]
+    pub similarity: f64,
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Computes summary statistics of the diff, e.g. to log a one-line summary of it.
+    pub fn stats(&self) -> DiffStats {
+        let mut visitor = StatsVisitor::default();
+        self.walk(&mut visitor);
+
+        DiffStats {
+            similarity: self.similarity(),
+            ..visitor.stats
+        }
+    }
+}
+
+#[derive(Default)]
+struct StatsVisitor {
+    stats: DiffStats,
+    depth: usize,
+
+    /// Whether each of the sequences being walked is the single value of a `Some`, an `Ok` or an
+    /// `Err`, which is counted neither as an element nor as a level of depth
+    wrapped: Vec<bool>,
+}
+
+impl StatsVisitor {
+    fn change(&mut self, depth: usize) {
+        self.stats.depth = self.stats.depth.max(depth);
+    }
+
+    fn nested(&mut self, diff: &Diff) {
+        self.depth += 1;
+        diff.walk(self);
+        self.depth -= 1;
+    }
+}
+
+impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for StatsVisitor {
+    fn field_changed(&mut self, _field: &'static str, diff: &Diff<'mem, 'facet>) {
+        self.stats.fields += 1;
+        self.nested(diff);
+    }
+
+    fn field_unchanged(&mut self, _field: &'static str, _value: Peek<'mem, 'facet>) {
+        self.stats.unchanged += 1;
+    }
+
+    fn field_added(&mut self, _field: &'static str, _value: Peek<'mem, 'facet>) {
+        self.stats.fields += 1;
+        self.stats.added += 1;
+        self.change(self.depth + 1);
+    }
+
+    fn field_removed(&mut self, _field: &'static str, _value: Peek<'mem, 'facet>) {
+        self.stats.fields += 1;
+        self.stats.removed += 1;
+        self.change(self.depth + 1);
+    }
+
+    fn entry_changed(&mut self, _key: &str, diff: &Diff<'mem, 'facet>) {
+        self.stats.fields += 1;
+        self.nested(diff);
+    }
+
+    fn entry_unchanged(&mut self, _key: &str, _value: Peek<'mem, 'facet>) {
+        self.stats.unchanged += 1;
+    }
+
+    fn entry_added(&mut self, _key: &str, _value: Peek<'mem, 'facet>) {
+        self.stats.fields += 1;
+        self.stats.added += 1;
+        self.change(self.depth + 1);
+    }
+
+    fn entry_removed(&mut self, _key: &str, _value: Peek<'mem, 'facet>) {
+        self.stats.fields += 1;
+        self.stats.removed += 1;
+        self.change(self.depth + 1);
+    }
+
+    fn element_kept(&mut self, _value: Peek<'mem, 'facet>) {
+        self.stats.unchanged += 1;
+    }
+
+    fn element_added(&mut self, _value: Peek<'mem, 'facet>) {
+        self.stats.elements += 1;
+        self.stats.added += 1;
+        self.change(self.depth + 1);
+    }
+
+    fn element_removed(&mut self, _value: Peek<'mem, 'facet>) {
+        self.stats.elements += 1;
+        self.stats.removed += 1;
+        self.change(self.depth + 1);
+    }
+
+    fn enter_sequence(&mut self, from: &'static Shape, _to: &'static Shape) {
+        self.wrapped.push(diff::is_wrapper(from));
+    }
+
+    fn exit_sequence(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.wrapped.pop();
+    }
+
+    fn element_replaced(&mut self, diff: &Diff<'mem, 'facet>) {
+        if self.wrapped.last() == Some(&true) {
+            diff.walk(self);
+        } else {
+            self.stats.elements += 1;
+            self.nested(diff);
+        }
+    }
+
+    fn became_present(&mut self, _value: Peek<'mem, 'facet>) {
+        self.stats.added += 1;
+        self.change(self.depth);
+    }
+
+    fn became_absent(&mut self, _value: Peek<'mem, 'facet>) {
+        self.stats.removed += 1;
+        self.change(self.depth);
+    }
+
+    fn outcome_flipped(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>, _ok: bool) {
+        self.stats.changed += 1;
+        self.change(self.depth);
+    }
+
+    fn back_reference(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>, _cycle: bool) {
+        self.stats.changed += 1;
+        self.change(self.depth);
+    }
+
+    fn inaccessible(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>) {
+        self.stats.changed += 1;
+        self.change(self.depth);
+    }
+
+    fn redacted(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.stats.changed += 1;
+        self.change(self.depth);
+    }
+
+    fn leaf_replace(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>) {
+        self.stats.changed += 1;
+        self.change(self.depth);
+    }
+}
+
+impl Display for DiffStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} changed, {} added, {} removed, {} unchanged",
+            self.changed, self.added, self.removed, self.unchanged
+        )
+    }
+}
@@ -0,0 +1,245 @@
+use std::fmt::Write;
+
+use facet::{Field, FieldFlags, Shape, Type, UserType};
+
+use crate::diff;
+
+/// A segment of the path to a value in a rendering of a diff.
+pub(crate) enum Segment {
+    Field(&'static str),
+    Key(String),
+    Index(usize),
+
+    /// A field of a tuple or tuple variant
+    Position(usize),
+
+    /// A changed element, at different indices in the two sequences
+    Moved(usize, usize),
+}
+
+impl Segment {
+    /// Appends the segment to a path, e.g. `.name`, `["a key"]`, `[3]` or `.0`.
+    fn write(&self, path: &mut String) {
+        match self {
+            Segment::Field(name) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(name);
+            }
+            Segment::Key(key) => {
+                let simple = !key.is_empty()
+                    && key
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+
+                if !simple {
+                    let _ = write!(path, "[{key:?}]");
+                } else {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                }
+            }
+            Segment::Index(index) => {
+                let _ = write!(path, "[{index}]");
+            }
+            Segment::Position(index) => {
+                let _ = write!(path, ".{index}");
+            }
+            Segment::Moved(from, to) => {
+                let _ = write!(path, "[{from}→{to}]");
+            }
+        }
+    }
+
+    /// The segment on its own, e.g. to label a value in a tree. Keys are left as they are.
+    pub(crate) fn label(&self) -> String {
+        match self {
+            Segment::Key(key) => key.clone(),
+            segment => {
+                let mut label = String::new();
+                segment.write(&mut label);
+                label
+            }
+        }
+    }
+}
+
+/// A struct, enum variant or map whose fields or entries are being visited.
+struct Parent {
+    from: &'static Shape,
+    to: &'static Shape,
+    from_variant: Option<&'static str>,
+    to_variant: Option<&'static str>,
+}
+
+/// How the elements of a sequence appear in paths.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Elements {
+    /// By their index, e.g. `[3]`
+    Indexed,
+
+    /// By their position, e.g. `.0`, for the fields of tuples and tuple variants
+    Positional,
+
+    /// Not at all, for the single value in a `Some`, an `Ok` or an `Err`, cf. [`diff::is_wrapper`]
+    Wrapped,
+}
+
+/// The position of the next element on each side of a sequence.
+struct Cursor {
+    from: usize,
+    to: usize,
+    elements: Elements,
+}
+
+/// Keeps track of the path to the values visited by a renderer, cf. [`DiffVisitor`](crate::DiffVisitor).
+///
+/// Renderers call the `enter_*` and `exit_*` methods from the corresponding visitor methods, and
+/// push a segment before rendering a field, an entry or an element.
+#[derive(Default)]
+pub(crate) struct Tracker {
+    /// The segments of the path, values without a segment of their own are `None`
+    path: Vec<Option<Segment>>,
+    parents: Vec<Parent>,
+    cursors: Vec<Cursor>,
+}
+
+/// The fields of a struct or enum variant.
+pub(crate) fn fields(shape: &'static Shape, variant: Option<&str>) -> &'static [Field] {
+    match (shape.ty, variant) {
+        (Type::User(UserType::Struct(ty)), _) => ty.fields,
+        (Type::User(UserType::Enum(ty)), Some(variant)) => ty
+            .variants
+            .iter()
+            .find(|x| x.name == variant)
+            .map(|x| x.data.fields)
+            .unwrap_or_default(),
+        _ => &[],
+    }
+}
+
+/// Whether a field of a struct or enum variant is sensitive (`#[facet(sensitive)]`).
+fn is_sensitive(shape: &'static Shape, variant: Option<&str>, name: &str) -> bool {
+    fields(shape, variant)
+        .iter()
+        .any(|field| field.name == name && field.flags.contains(FieldFlags::SENSITIVE))
+}
+
+impl Tracker {
+    /// The path to the current value, e.g. `spec.containers[0].image`.
+    pub(crate) fn path(&self) -> String {
+        let mut path = String::new();
+        for segment in self.path.iter().flatten() {
+            segment.write(&mut path);
+        }
+        path
+    }
+
+    /// The last segment of the path to the current value.
+    pub(crate) fn last(&self) -> Option<&Segment> {
+        self.path.last()?.as_ref()
+    }
+
+    /// Enters two structs, enum variants or maps.
+    pub(crate) fn enter(
+        &mut self,
+        (from, to): (&'static Shape, &'static Shape),
+        (from_variant, to_variant): (Option<&'static str>, Option<&'static str>),
+    ) {
+        self.parents.push(Parent {
+            from,
+            to,
+            from_variant,
+            to_variant,
+        });
+    }
+
+    pub(crate) fn exit(&mut self) {
+        self.parents.pop();
+    }
+
+    /// Enters two sequences, and returns how their elements appear in paths.
+    pub(crate) fn enter_sequence(&mut self, from: &'static Shape) -> Elements {
+        let elements = match from.ty {
+            _ if diff::is_wrapper(from) => Elements::Wrapped,
+            Type::User(UserType::Struct(_) | UserType::Enum(_)) => Elements::Positional,
+            _ => Elements::Indexed,
+        };
+
+        self.cursors.push(Cursor {
+            from: 0,
+            to: 0,
+            elements,
+        });
+        elements
+    }
+
+    /// Exits two sequences, and returns how their elements appeared in paths.
+    pub(crate) fn exit_sequence(&mut self) -> Option<Elements> {
+        self.cursors.pop().map(|cursor| cursor.elements)
+    }
+
+    /// Moves to a field.
+    pub(crate) fn push_field(&mut self, name: &'static str) {
+        self.path.push(Some(Segment::Field(name)));
+    }
+
+    /// Moves to an entry.
+    pub(crate) fn push_entry(&mut self, key: &str) {
+        self.path.push(Some(Segment::Key(key.to_string())));
+    }
+
+    /// Moves to an element, at its position in `from` if it is there and in `to` otherwise. Changed
+    /// elements that moved are at both positions.
+    ///
+    /// Returns whether the element has a segment of its own, which is not the case e.g. for the
+    /// value in a `Some`.
+    pub(crate) fn push_element(&mut self, from: bool, to: bool, changed: bool) -> bool {
+        let Some(cursor) = self
+            .cursors
+            .last_mut()
+            .filter(|cursor| cursor.elements != Elements::Wrapped)
+        else {
+            self.path.push(None);
+            return false;
+        };
+
+        let index = if from { cursor.from } else { cursor.to };
+        let moved = from && to && changed && cursor.from != cursor.to;
+        let segment = match cursor.elements {
+            Elements::Positional => Segment::Position(index),
+            _ if moved => Segment::Moved(cursor.from, cursor.to),
+            _ => Segment::Index(index),
+        };
+        cursor.from += from as usize;
+        cursor.to += to as usize;
+
+        self.path.push(Some(segment));
+        true
+    }
+
+    /// Moves back to the parent of the current value.
+    pub(crate) fn pop(&mut self) {
+        self.path.pop();
+    }
+
+    /// Whether the current value is a sensitive field of its struct or enum variant, on either
+    /// side. This also applies to the fields of tuples, and to the entries of a map that is
+    /// compared to a struct.
+    pub(crate) fn is_sensitive(&self) -> bool {
+        let name = match self.last() {
+            Some(Segment::Field(name)) => name.to_string(),
+            Some(Segment::Key(key)) => key.clone(),
+            Some(Segment::Position(index)) => index.to_string(),
+            _ => return false,
+        };
+
+        self.parents.last().is_some_and(|parent| {
+            is_sensitive(parent.from, parent.from_variant, &name)
+                || is_sensitive(parent.to, parent.to_variant, &name)
+        })
+    }
+}
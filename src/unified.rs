@@ -0,0 +1,368 @@
+use std::fmt::Display;
+
+use facet::Shape;
+use facet_reflect::Peek;
+
+use crate::{
+    Diff, DiffVisitor, diff,
+    elide::Limits,
+    tracking::{Segment, Tracker},
+};
+
+/// A rendering of a [`Diff`] as a list of changed paths, in the style of a unified diff.
+///
+/// Each line starts with `-` for removed values, `+` for added values, `~` for values that could not
+/// be compared and a space for unchanged values, e.g.
+///
+/// ```text
+///   user.name: Ann
+/// - user.age: 30
+/// + user.age: 31
+/// ```
+///
+/// Only the unchanged values around a change are shown, cf. [`Unified::context`].
+pub struct Unified<'a, 'mem, 'facet> {
+    diff: &'a Diff<'mem, 'facet>,
+    context: usize,
+    colors: bool,
+    limits: Limits,
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Renders this diff as a unified list of changed paths, cf. [`Unified`].
+    pub fn unified(&self) -> Unified<'_, 'mem, 'facet> {
+        Unified {
+            diff: self,
+            context: 3,
+            colors: true,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl<'a, 'mem, 'facet> Unified<'a, 'mem, 'facet> {
+    /// Sets the number of unchanged values that are shown around each change, 3 by default.
+    pub fn context(mut self, context: usize) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Sets whether the lines are colored with ANSI escape codes, which they are by default.
+    pub fn colors(mut self, colors: bool) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Elides the values, or the lines of values, that are longer than `max_len` characters, cf. [`Tree::max_value_len`](crate::Tree::max_value_len).
+    pub fn max_value_len(mut self, max_len: usize) -> Self {
+        self.limits.max_len = Some(max_len);
+        self
+    }
+
+    /// Elides the values that span more than `max_lines` lines.
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.limits.max_lines = Some(max_lines);
+        self
+    }
+
+    /// Wraps the lines of values at `width` characters, e.g. the width of the terminal.
+    pub fn width(mut self, width: usize) -> Self {
+        self.limits.width = Some(width);
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Unchanged,
+    Removed,
+    Added,
+    Note,
+}
+
+/// A value at a path, which is rendered as one or more lines.
+pub(crate) struct Item {
+    pub(crate) kind: Kind,
+    pub(crate) path: String,
+    pub(crate) text: String,
+
+    /// Whether the value is a string
+    pub(crate) string: bool,
+
+    /// The index of the element, if the value is an element of a sequence
+    pub(crate) index: Option<usize>,
+}
+
+#[derive(Default)]
+struct Lines {
+    tracker: Tracker,
+    items: Vec<Item>,
+    limits: Limits,
+}
+
+impl Lines {
+    fn push(&mut self, kind: Kind, text: String) {
+        let path = self.tracker.path();
+        let index = match self.tracker.last() {
+            Some(Segment::Index(index)) => Some(*index),
+            _ => None,
+        };
+
+        self.items.push(Item {
+            kind,
+            path,
+            text,
+            string: false,
+            index,
+        });
+    }
+
+    fn value(&mut self, kind: Kind, value: Peek) {
+        self.push(kind, self.limits.format(value));
+
+        if let Some(item) = self.items.last_mut() {
+            item.string = diff::as_str(value).is_some();
+        }
+    }
+
+    /// Formats an unchanged value, unless it is sensitive, since those are not shown even as
+    /// context.
+    fn unchanged(&mut self, value: Peek) {
+        match self.tracker.is_sensitive() {
+            true => self.push(Kind::Unchanged, "<redacted>".into()),
+            false => self.value(Kind::Unchanged, value),
+        }
+    }
+
+    fn field(&mut self, name: &'static str, f: impl FnOnce(&mut Self)) {
+        self.tracker.push_field(name);
+        f(self);
+        self.tracker.pop();
+    }
+
+    fn entry(&mut self, key: &str, f: impl FnOnce(&mut Self)) {
+        self.tracker.push_entry(key);
+        f(self);
+        self.tracker.pop();
+    }
+
+    /// Visits an element, cf. [`Tracker::push_element`].
+    fn element(&mut self, from: bool, to: bool, changed: bool, f: impl FnOnce(&mut Self)) {
+        self.tracker.push_element(from, to, changed);
+        f(self);
+        self.tracker.pop();
+    }
+}
+
+impl<'mem, 'facet> DiffVisitor<'mem, 'facet> for Lines {
+    fn enter_struct(&mut self, from: &'static Shape, to: &'static Shape) {
+        self.tracker.enter((from, to), (None, None));
+    }
+
+    fn exit_struct(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.tracker.exit();
+    }
+
+    fn enter_variant(
+        &mut self,
+        from: &'static Shape,
+        to: &'static Shape,
+        from_variant: &'static str,
+        to_variant: &'static str,
+    ) {
+        self.tracker
+            .enter((from, to), (Some(from_variant), Some(to_variant)));
+    }
+
+    fn exit_variant(
+        &mut self,
+        _from: &'static Shape,
+        _to: &'static Shape,
+        _from_variant: &'static str,
+        _to_variant: &'static str,
+    ) {
+        self.tracker.exit();
+    }
+
+    fn field_changed(&mut self, field: &'static str, diff: &Diff<'mem, 'facet>) {
+        self.field(field, |lines| diff.walk(lines));
+    }
+
+    fn field_unchanged(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        self.field(field, |lines| lines.unchanged(value));
+    }
+
+    fn field_added(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        self.field(field, |lines| lines.value(Kind::Added, value));
+    }
+
+    fn field_removed(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {
+        self.field(field, |lines| lines.value(Kind::Removed, value));
+    }
+
+    fn enter_map(&mut self, from: &'static Shape, to: &'static Shape) {
+        self.tracker.enter((from, to), (None, None));
+    }
+
+    fn exit_map(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.tracker.exit();
+    }
+
+    fn entry_changed(&mut self, key: &str, diff: &Diff<'mem, 'facet>) {
+        self.entry(key, |lines| diff.walk(lines));
+    }
+
+    fn entry_unchanged(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+        self.entry(key, |lines| lines.unchanged(value));
+    }
+
+    fn entry_added(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+        self.entry(key, |lines| lines.value(Kind::Added, value));
+    }
+
+    fn entry_removed(&mut self, key: &str, value: Peek<'mem, 'facet>) {
+        self.entry(key, |lines| lines.value(Kind::Removed, value));
+    }
+
+    fn enter_sequence(&mut self, from: &'static Shape, _to: &'static Shape) {
+        self.tracker.enter_sequence(from);
+    }
+
+    fn exit_sequence(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.tracker.exit_sequence();
+    }
+
+    fn element_kept(&mut self, value: Peek<'mem, 'facet>) {
+        self.element(true, true, false, |lines| lines.unchanged(value));
+    }
+
+    fn element_added(&mut self, value: Peek<'mem, 'facet>) {
+        self.element(false, true, false, |lines| lines.value(Kind::Added, value));
+    }
+
+    fn element_removed(&mut self, value: Peek<'mem, 'facet>) {
+        self.element(true, false, false, |lines| {
+            lines.value(Kind::Removed, value)
+        });
+    }
+
+    fn element_replaced(&mut self, diff: &Diff<'mem, 'facet>) {
+        self.element(true, true, true, |lines| diff.walk(lines));
+    }
+
+    fn became_present(&mut self, value: Peek<'mem, 'facet>) {
+        self.value(Kind::Added, value);
+    }
+
+    fn became_absent(&mut self, value: Peek<'mem, 'facet>) {
+        self.value(Kind::Removed, value);
+    }
+
+    fn back_reference(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>, cycle: bool) {
+        if cycle {
+            self.push(Kind::Note, "<back-reference: cycle>".into());
+        } else {
+            self.push(Kind::Note, "<back-reference: compared elsewhere>".into());
+        }
+    }
+
+    fn inaccessible(&mut self, _from: Peek<'mem, 'facet>, _to: Peek<'mem, 'facet>) {
+        self.push(Kind::Note, "<inaccessible>".into());
+    }
+
+    fn redacted(&mut self, _from: &'static Shape, _to: &'static Shape) {
+        self.push(Kind::Removed, "<redacted>".into());
+        self.push(Kind::Added, "<redacted (changed)>".into());
+    }
+
+    fn outcome_flipped(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>, _ok: bool) {
+        self.value(Kind::Removed, from);
+        self.value(Kind::Added, to);
+    }
+
+    fn leaf_replace(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {
+        let (from_text, to_text) = self.limits.format_pair(from, to);
+        let string = diff::as_str(from).is_some() && diff::as_str(to).is_some();
+
+        for (kind, text) in [(Kind::Removed, from_text), (Kind::Added, to_text)] {
+            self.push(kind, text);
+            if let Some(item) = self.items.last_mut() {
+                item.string = string;
+            }
+        }
+    }
+}
+
+/// The values of a diff at each path, with fields in declaration order and entries sorted by key.
+pub(crate) fn items(diff: &Diff, limits: Limits) -> Vec<Item> {
+    let mut lines = Lines {
+        limits,
+        ..Default::default()
+    };
+    diff.walk(&mut lines);
+    lines.items
+}
+
+impl<'a, 'mem, 'facet> Display for Unified<'a, 'mem, 'facet> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let items = items(self.diff, self.limits);
+
+        let changes = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.kind != Kind::Unchanged)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        // The changes are in order, so the first one at or after `i - context` is the closest
+        let near_change = |i: usize| {
+            let first = changes.partition_point(|change| *change < i.saturating_sub(self.context));
+            changes
+                .get(first)
+                .is_some_and(|change| *change <= i.saturating_add(self.context))
+        };
+
+        let (reset, dim) = if self.colors {
+            ("\x1b[m", "\x1b[2m")
+        } else {
+            ("", "")
+        };
+        let mut elided = 0;
+
+        for (i, item) in items.iter().enumerate() {
+            if !near_change(i) {
+                elided += 1;
+                continue;
+            }
+
+            if elided > 0 {
+                writeln!(f, "{dim}@@ {elided} unchanged @@{reset}")?;
+                elided = 0;
+            }
+
+            let (prefix, color) = match item.kind {
+                Kind::Unchanged => (' ', ""),
+                Kind::Removed => ('-', "\x1b[31m"),
+                Kind::Added => ('+', "\x1b[32m"),
+                Kind::Note => ('~', "\x1b[36m"),
+            };
+            let (color, reset) = if self.colors && !color.is_empty() {
+                (color, reset)
+            } else {
+                ("", "")
+            };
+
+            for (n, line) in item.text.split('\n').enumerate() {
+                match (n, item.path.is_empty()) {
+                    (0, false) => writeln!(f, "{color}{prefix} {}: {line}{reset}", item.path)?,
+                    _ => writeln!(f, "{color}{prefix} {line}{reset}")?,
+                }
+            }
+        }
+
+        if elided > 0 {
+            writeln!(f, "{dim}@@ {elided} unchanged @@{reset}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+use facet::{Field, Shape};
+use facet_reflect::Peek;
+
+use crate::{
+    diff::{Diff, Value},
+    positional::{Common, Positional},
+    sequences::{ReplaceGroup, Updates, UpdatesGroup},
+    tracking,
+};
+
+/// A visitor over the tree of a [`Diff`], driven by [`Diff::walk`].
+///
+/// All methods have a default implementation. The methods that receive a nested [`Diff`]
+/// (`field_changed`, `entry_changed` and `element_replaced`) walk into it by default; when overriding them, call
+/// [`Diff::walk`] on the nested diff to keep descending, e.g. after pushing the field name onto a path.
+#[allow(unused_variables)]
+pub trait DiffVisitor<'mem, 'facet> {
+    /// Called before the fields of two structs are visited.
+    fn enter_struct(&mut self, from: &'static Shape, to: &'static Shape) {}
+
+    /// Called after the fields of two structs are visited.
+    fn exit_struct(&mut self, from: &'static Shape, to: &'static Shape) {}
+
+    /// Called before the fields of two enum variants are visited.
+    ///
+    /// The variants can differ if they have fields in common.
+    fn enter_variant(
+        &mut self,
+        from: &'static Shape,
+        to: &'static Shape,
+        from_variant: &'static str,
+        to_variant: &'static str,
+    ) {
+    }
+
+    /// Called after the fields of two enum variants are visited.
+    fn exit_variant(
+        &mut self,
+        from: &'static Shape,
+        to: &'static Shape,
+        from_variant: &'static str,
+        to_variant: &'static str,
+    ) {
+    }
+
+    /// A field that is in both values, but differs between them.
+    fn field_changed(&mut self, field: &'static str, diff: &Diff<'mem, 'facet>) {
+        diff.walk(self);
+    }
+
+    /// A field that is in both values and is equal between them.
+    fn field_unchanged(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {}
+
+    /// A field that is only in the `to` value.
+    fn field_added(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {}
+
+    /// A field that is only in the `from` value.
+    fn field_removed(&mut self, field: &'static str, value: Peek<'mem, 'facet>) {}
+
+    /// Called before the entries of two maps, or of a struct and a map, are visited.
+    fn enter_map(&mut self, from: &'static Shape, to: &'static Shape) {}
+
+    /// Called after the entries of two maps are visited.
+    fn exit_map(&mut self, from: &'static Shape, to: &'static Shape) {}
+
+    /// An entry that is in both values, but differs between them.
+    fn entry_changed(&mut self, key: &str, diff: &Diff<'mem, 'facet>) {
+        diff.walk(self);
+    }
+
+    /// An entry that is in both values and is equal between them.
+    fn entry_unchanged(&mut self, key: &str, value: Peek<'mem, 'facet>) {}
+
+    /// An entry that is only in the `to` value.
+    fn entry_added(&mut self, key: &str, value: Peek<'mem, 'facet>) {}
+
+    /// An entry that is only in the `from` value.
+    fn entry_removed(&mut self, key: &str, value: Peek<'mem, 'facet>) {}
+
+    /// Called before the elements of two sequences are visited.
+    ///
+    /// The fields of tuples are also visited as elements of a sequence.
+    fn enter_sequence(&mut self, from: &'static Shape, to: &'static Shape) {}
+
+    /// Called after the elements of two sequences are visited.
+    fn exit_sequence(&mut self, from: &'static Shape, to: &'static Shape) {}
+
+    /// An element that is in both sequences.
+    fn element_kept(&mut self, value: Peek<'mem, 'facet>) {}
+
+    /// An element that is only in the `to` sequence.
+    fn element_added(&mut self, value: Peek<'mem, 'facet>) {}
+
+    /// An element that is only in the `from` sequence.
+    fn element_removed(&mut self, value: Peek<'mem, 'facet>) {}
+
+    /// An element that was changed in place.
+    fn element_replaced(&mut self, diff: &Diff<'mem, 'facet>) {
+        diff.walk(self);
+    }
+
+    /// An option that went from `None` to `Some(value)`.
+    fn became_present(&mut self, value: Peek<'mem, 'facet>) {}
+
+    /// An option that went from `Some(value)` to `None`.
+    fn became_absent(&mut self, value: Peek<'mem, 'facet>) {}
+
+    /// Two values behind pointers that were already compared elsewhere, or that are being compared
+    /// further up if `cycle` is set.
+    fn back_reference(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>, cycle: bool) {}
+
+    /// Two values, one of which is behind a lock or a cell that cannot be accessed.
+    fn inaccessible(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {}
+
+    /// The values of a sensitive field, which differ but are not part of the diff.
+    fn redacted(&mut self, from: &'static Shape, to: &'static Shape) {}
+
+    /// A result that went from `Err` to `Ok` (if `ok` is set), or from `Ok` to `Err`.
+    fn outcome_flipped(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>, ok: bool) {}
+
+    /// Two values that are unequal, and that we know nothing else about.
+    fn leaf_replace(&mut self, from: Peek<'mem, 'facet>, to: Peek<'mem, 'facet>) {}
+}
+
+/// A field of a struct or an entry of a map, cf. [`Diff::walk`].
+enum Change<'a, 'mem, 'facet> {
+    Changed(&'a Diff<'mem, 'facet>),
+    Removed(Peek<'mem, 'facet>),
+    Added(Peek<'mem, 'facet>),
+    Unchanged(Peek<'mem, 'facet>),
+}
+
+impl<'mem, 'facet> Diff<'mem, 'facet> {
+    /// Walks the tree of this diff, calling the corresponding methods on `visitor`.
+    ///
+    /// Walking an equal diff does not call any method. The fields of structs are visited in
+    /// declaration order, and the entries of maps in the order of their keys.
+    pub fn walk<V: DiffVisitor<'mem, 'facet> + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            Diff::Equal => {}
+            Diff::Replace { from, to } => visitor.leaf_replace(*from, *to),
+            Diff::User {
+                from,
+                to,
+                from_variant,
+                to_variant,
+                value,
+            } => {
+                match (from_variant, to_variant) {
+                    (Some(from_variant), Some(to_variant)) => {
+                        visitor.enter_variant(from, to, from_variant, to_variant)
+                    }
+                    _ => visitor.enter_struct(from, to),
+                }
+
+                match value {
+                    Value::Tuple { updates } => {
+                        visitor.enter_sequence(from, to);
+                        updates.walk(visitor);
+                        visitor.exit_sequence(from, to);
+                    }
+                    Value::Struct {
+                        updates,
+                        deletions,
+                        insertions,
+                        unchanged,
+                    } => {
+                        // Fields are visited in declaration order, whether they changed or not.
+                        // Those that are only in the `to` variant come at their position in it.
+                        let from_fields = tracking::fields(from, *from_variant);
+                        let to_fields = tracking::fields(to, *to_variant);
+                        let position = |name: &str| {
+                            let position =
+                                |fields: &[Field]| fields.iter().position(|x| x.name == name);
+                            position(from_fields)
+                                .or_else(|| position(to_fields))
+                                .unwrap_or(usize::MAX)
+                        };
+
+                        let mut fields = Vec::new();
+                        fields.extend(updates.iter().map(|(k, v)| (*k, Change::Changed(v))));
+                        fields.extend(deletions.iter().map(|(k, v)| (*k, Change::Removed(*v))));
+                        fields.extend(insertions.iter().map(|(k, v)| (*k, Change::Added(*v))));
+                        fields.extend(unchanged.iter().map(|(k, v)| (*k, Change::Unchanged(*v))));
+                        fields.sort_by_key(|(field, _)| position(field));
+
+                        for (field, change) in fields {
+                            match change {
+                                Change::Changed(diff) => visitor.field_changed(field, diff),
+                                Change::Removed(value) => visitor.field_removed(field, value),
+                                Change::Added(value) => visitor.field_added(field, value),
+                                Change::Unchanged(value) => visitor.field_unchanged(field, value),
+                            }
+                        }
+                    }
+                }
+
+                match (from_variant, to_variant) {
+                    (Some(from_variant), Some(to_variant)) => {
+                        visitor.exit_variant(from, to, from_variant, to_variant)
+                    }
+                    _ => visitor.exit_struct(from, to),
+                }
+            }
+            Diff::Sequence { from, to, updates } => {
+                visitor.enter_sequence(from, to);
+                updates.walk(visitor);
+                visitor.exit_sequence(from, to);
+            }
+            Diff::Array { from, to, updates } => {
+                visitor.enter_sequence(from, to);
+                updates.walk(visitor);
+                visitor.exit_sequence(from, to);
+            }
+            Diff::Map { from, to, updates } => {
+                visitor.enter_map(from, to);
+
+                // Entries are visited in the order of their keys, whether they changed or not
+                let mut entries = Vec::new();
+                entries.extend(updates.updates.iter().map(|(k, v)| (k, Change::Changed(v))));
+                entries.extend(
+                    updates
+                        .deletions
+                        .iter()
+                        .map(|(k, v)| (k, Change::Removed(*v))),
+                );
+                entries.extend(
+                    updates
+                        .insertions
+                        .iter()
+                        .map(|(k, v)| (k, Change::Added(*v))),
+                );
+                entries.extend(
+                    updates
+                        .unchanged
+                        .iter()
+                        .map(|(k, v)| (k, Change::Unchanged(*v))),
+                );
+                entries.sort_by_key(|(key, _)| *key);
+
+                for (key, change) in entries {
+                    match change {
+                        Change::Changed(diff) => visitor.entry_changed(key, diff),
+                        Change::Removed(value) => visitor.entry_removed(key, value),
+                        Change::Added(value) => visitor.entry_added(key, value),
+                        Change::Unchanged(value) => visitor.entry_unchanged(key, value),
+                    }
+                }
+
+                visitor.exit_map(from, to);
+            }
+            Diff::Presence { value, present, .. } => {
+                if *present {
+                    visitor.became_present(*value);
+                } else {
+                    visitor.became_absent(*value);
+                }
+            }
+            Diff::BackReference { from, to, cycle } => visitor.back_reference(*from, *to, *cycle),
+            Diff::Inaccessible { from, to } => visitor.inaccessible(*from, *to),
+            Diff::Redacted { from, to } => visitor.redacted(from, to),
+            Diff::Outcome {
+                from_value,
+                to_value,
+                ok,
+                ..
+            } => visitor.outcome_flipped(*from_value, *to_value, *ok),
+        }
+    }
+}
+
+impl<'mem, 'facet> Updates<'mem, 'facet> {
+    fn walk<V: DiffVisitor<'mem, 'facet> + ?Sized>(&self, visitor: &mut V) {
+        if let Some(group) = &self.0.first {
+            group.walk(visitor);
+        }
+
+        for (values, group) in &self.0.values {
+            for value in values {
+                visitor.element_kept(*value);
+            }
+
+            group.walk(visitor);
+        }
+
+        if let Some(values) = &self.0.last {
+            for value in values {
+                visitor.element_kept(*value);
+            }
+        }
+    }
+}
+
+impl<'mem, 'facet> Positional<'mem, 'facet> {
+    fn walk<V: DiffVisitor<'mem, 'facet> + ?Sized>(&self, visitor: &mut V) {
+        for (_, element) in self.common() {
+            match element {
+                Common::Changed(diff) => visitor.element_replaced(diff),
+                Common::Unchanged(value) => visitor.element_kept(value),
+            }
+        }
+
+        for (_, value) in &self.deletions {
+            visitor.element_removed(*value);
+        }
+
+        for (_, value) in &self.insertions {
+            visitor.element_added(*value);
+        }
+    }
+}
+
+impl<'mem, 'facet> UpdatesGroup<'mem, 'facet> {
+    fn walk<V: DiffVisitor<'mem, 'facet> + ?Sized>(&self, visitor: &mut V) {
+        if let Some(group) = &self.0.first {
+            group.walk(visitor);
+        }
+
+        for (diffs, group) in &self.0.values {
+            for diff in diffs {
+                visitor.element_replaced(diff);
+            }
+
+            group.walk(visitor);
+        }
+
+        if let Some(diffs) = &self.0.last {
+            for diff in diffs {
+                visitor.element_replaced(diff);
+            }
+        }
+    }
+}
+
+impl<'mem, 'facet> ReplaceGroup<'mem, 'facet> {
+    fn walk<V: DiffVisitor<'mem, 'facet> + ?Sized>(&self, visitor: &mut V) {
+        for value in &self.removals {
+            visitor.element_removed(*value);
+        }
+
+        for value in &self.additions {
+            visitor.element_added(*value);
+        }
+    }
+}
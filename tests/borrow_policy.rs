@@ -0,0 +1,149 @@
+//! The borrow policies need a value behind a cell or a lock that cannot be accessed, and `facet`
+//! does not implement `Facet` for cells or locks. This test implements it for stand-ins, which takes
+//! `unsafe` code that the library itself forbids.
+
+use std::{cell::RefCell, sync::Mutex};
+
+use facet::{
+    Def, Facet, GenericPtr, KnownPointer, PointerDef, PointerFlags, PointerVTable, PtrConst, Shape,
+    Type, UserType, ValueVTable, value_vtable,
+};
+use facet_diff::{BorrowPolicy, Diff, DiffError, Differ, FacetDiff};
+
+/// A `RefCell` whose shape does not let us borrow the inner value.
+struct Guarded(#[allow(dead_code)] RefCell<u32>);
+
+unsafe impl<'a> Facet<'a> for Guarded {
+    const VTABLE: &'static ValueVTable =
+        &const { value_vtable!(Guarded, |f, _opts| write!(f, "Guarded")) };
+
+    const SHAPE: &'static Shape = &const {
+        Shape::builder_for_sized::<Self>()
+            .type_identifier("Guarded")
+            .ty(Type::User(UserType::Opaque))
+            .def(Def::Pointer(
+                PointerDef::builder()
+                    .pointee(|| u32::SHAPE)
+                    .flags(PointerFlags::EMPTY)
+                    .known(KnownPointer::RefCell)
+                    .vtable(&const { PointerVTable::builder().build() })
+                    .build(),
+            ))
+            .build()
+    };
+}
+
+#[derive(Facet)]
+struct Account {
+    name: String,
+    balance: Guarded,
+}
+
+fn account(name: &str, balance: u32) -> Account {
+    Account {
+        name: name.into(),
+        balance: Guarded(RefCell::new(balance)),
+    }
+}
+
+#[test]
+fn inaccessible_values_follow_the_borrow_policy() {
+    let (a, b) = (account("alice", 1), account("alice", 2));
+
+    // Reported by default
+    assert_eq!(a.diff(&b).compact().to_string(), "balance: <inaccessible>");
+    assert_eq!(a.diff(&b).stats().changed, 1);
+
+    let diff = Differ::new()
+        .borrow_policy(BorrowPolicy::Report)
+        .diff(&a.balance, &b.balance)
+        .unwrap();
+    assert!(matches!(diff, Diff::Inaccessible { .. }));
+
+    // Skipped
+    let diff = Diff::with_policy(&a, &b, BorrowPolicy::Skip).unwrap();
+    assert!(diff.is_equal());
+    let c = account("bob", 1);
+    let diff = Diff::with_policy(&a, &c, BorrowPolicy::Skip).unwrap();
+    assert_eq!(diff.compact().to_string(), "name: \"alice\"→\"bob\"");
+
+    // Failed
+    let error = Differ::new()
+        .borrow_policy(BorrowPolicy::Error)
+        .diff(&a, &b)
+        .err()
+        .expect("expected the diff to fail");
+    assert_eq!(error, DiffError::Inaccessible(Guarded::SHAPE));
+    assert_eq!(error.to_string(), "cannot access the value inside Guarded");
+    assert!(Diff::with_policy(&a, &c, BorrowPolicy::Error).is_err());
+
+    // Ignored values are not accessed
+    let diff = Differ::new()
+        .borrow_policy(BorrowPolicy::Error)
+        .ignore("balance")
+        .diff(&a, &b)
+        .unwrap();
+    assert!(diff.is_equal());
+}
+
+/// A `Mutex`, whose shape lets us borrow the inner value, which must not be done without locking it.
+struct Locked(#[allow(dead_code)] Mutex<u32>);
+
+unsafe fn borrow_locked(_this: PtrConst<'_>) -> GenericPtr<'_> {
+    panic!("the value inside a lock was borrowed");
+}
+
+unsafe impl<'a> Facet<'a> for Locked {
+    const VTABLE: &'static ValueVTable =
+        &const { value_vtable!(Locked, |f, _opts| write!(f, "Locked")) };
+
+    const SHAPE: &'static Shape = &const {
+        Shape::builder_for_sized::<Self>()
+            .type_identifier("Locked")
+            .ty(Type::User(UserType::Opaque))
+            .def(Def::Pointer(
+                PointerDef::builder()
+                    .pointee(|| u32::SHAPE)
+                    .flags(PointerFlags::LOCK)
+                    .known(KnownPointer::Mutex)
+                    .vtable(&const { PointerVTable::builder().borrow_fn(borrow_locked).build() })
+                    .build(),
+            ))
+            .build()
+    };
+}
+
+#[test]
+fn locks_are_never_accessed() {
+    let a = Locked(Mutex::new(1));
+    let b = Locked(Mutex::new(1));
+
+    // Poisoned by a thread that panicked while holding it
+    std::thread::scope(|scope| {
+        let thread = scope.spawn(|| {
+            let _guard = b.0.lock().unwrap();
+            panic!("poisoning the lock");
+        });
+        assert!(thread.join().is_err());
+    });
+    assert!(b.0.is_poisoned());
+
+    let diff = a.diff(&b);
+    assert!(matches!(diff, Diff::Inaccessible { .. }));
+    assert_eq!(diff.compact().to_string(), "<inaccessible>");
+
+    assert!(
+        Diff::with_policy(&a, &b, BorrowPolicy::Skip)
+            .unwrap()
+            .is_equal()
+    );
+
+    let error = Diff::with_policy(&a, &b, BorrowPolicy::Error)
+        .err()
+        .expect("expected the diff to fail");
+    assert_eq!(error, DiffError::Inaccessible(Locked::SHAPE));
+
+    // Even when the lock is free
+    let c = Locked(Mutex::new(1));
+    assert!(matches!(a.diff(&c), Diff::Inaccessible { .. }));
+}
@@ -0,0 +1,228 @@
+//! Runs the `facet-diff` binary on files in a temporary directory.
+
+#![cfg(feature = "cli")]
+
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+/// Writes the files to a fresh directory, and runs the binary with the arguments in it.
+fn run(name: &str, files: &[(&str, &str)], args: &[&str]) -> Output {
+    let dir = std::env::temp_dir().join(format!("facet-diff-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for (file, contents) in files {
+        std::fs::write(dir.join(file), contents).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_facet-diff"))
+        .args(args)
+        .args(["--color", "never"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn exit_code_tells_whether_files_differ() {
+    let files = [
+        ("old.json", r#"{"name": "web", "port": 80}"#),
+        ("same.yaml", "name: web\nport: 80\n"),
+        ("new.toml", "name = \"web\"\nport = 8080\n"),
+    ];
+
+    let output = run("equal", &files, &["old.json", "same.yaml"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+
+    let output = run("differ", &files, &["old.json", "new.toml", "-f", "unified"]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "  name: web\n- port: 80\n+ port: 8080\n");
+
+    let output = run("ignored", &files, &["old.json", "new.toml", "-i", "port"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+}
+
+#[test]
+fn errors_exit_with_2() {
+    let files = [("old.json", "{}"), ("broken.json", "{\"a\": ")];
+
+    let output = run("usage", &files, &["old.json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("facet-diff: expected 2 files, got 1\n"));
+
+    let output = run("missing", &files, &["old.json", "missing.json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("facet-diff: missing.json: "));
+
+    let output = run("broken", &files, &["old.json", "broken.json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).starts_with("facet-diff: broken.json: "));
+
+    let output = run("format", &files, &["old.json", "old.xml"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("unknown format"));
+
+    let output = run("help", &files, &["--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("Usage: facet-diff"));
+}
+
+#[test]
+fn json_is_parsed_strictly() {
+    let files = [
+        (
+            "old.json",
+            r#"{"emoji": "\ud83d\ude00", "big": 9007199254740993}"#,
+        ),
+        ("new.json", r#"{"emoji": "😀", "big": 9007199254740992}"#),
+    ];
+
+    let output = run("json", &files, &["old.json", "new.json", "-f", "unified"]);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "- big: 9007199254740993\n+ big: 9007199254740992\n  emoji: 😀\n"
+    );
+}
+
+/// Applies a JSON Patch (RFC 6902) to a document, one operation after the other.
+fn apply(mut document: Value, patch: &Value) -> Value {
+    for operation in patch.as_array().unwrap() {
+        let mut segments = operation["path"]
+            .as_str()
+            .unwrap()
+            .split('/')
+            .skip(1)
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect::<Vec<_>>();
+        let value = operation.get("value").cloned();
+
+        let Some(last) = segments.pop() else {
+            document = value.unwrap();
+            continue;
+        };
+
+        let parent = segments
+            .iter()
+            .fold(&mut document, |parent, segment| match parent {
+                Value::Array(array) => &mut array[segment.parse::<usize>().unwrap()],
+                Value::Object(object) => object.get_mut(segment).unwrap(),
+                _ => panic!("{operation} goes through a scalar"),
+            });
+
+        match (operation["op"].as_str().unwrap(), parent) {
+            ("add", Value::Array(array)) => array.insert(last.parse().unwrap(), value.unwrap()),
+            ("remove", Value::Array(array)) => {
+                array.remove(last.parse().unwrap());
+            }
+            ("replace", Value::Array(array)) => {
+                array[last.parse::<usize>().unwrap()] = value.unwrap()
+            }
+            ("add", Value::Object(object)) => {
+                object.insert(last, value.unwrap());
+            }
+            ("remove", Value::Object(object)) => {
+                object.remove(&last).unwrap();
+            }
+            ("replace", Value::Object(object)) => {
+                *object.get_mut(&last).unwrap() = value.unwrap();
+            }
+            _ => panic!("cannot apply {operation}"),
+        }
+    }
+
+    document
+}
+
+/// Diffs two documents as a JSON Patch, and checks that applying it to `old` gives `expected`.
+fn check_patch(name: &str, old: &str, new: &str, args: &[&str], expected: &str) {
+    let files = [("old.json", old), ("new.json", new)];
+    let args = [&["old.json", "new.json", "-f", "json-patch"], args].concat();
+
+    let output = run(name, &files, &args);
+    assert!(
+        output.status.code() == Some(0) || output.status.code() == Some(1),
+        "{}",
+        stderr(&output)
+    );
+
+    let patch = serde_json::from_str::<Value>(&stdout(&output)).unwrap();
+    let old = serde_json::from_str::<Value>(old).unwrap();
+    let expected = serde_json::from_str::<Value>(expected).unwrap();
+    assert_eq!(apply(old, &patch), expected, "{patch:#}");
+}
+
+#[test]
+fn json_patch_turns_old_into_new() {
+    let cases = [
+        (
+            r#"{"name": "web", "ports": [80, 443], "env": {"DEBUG": "1", "a/b": true}}"#,
+            r#"{"name": "api", "ports": [443, 8080], "env": {"a/b": false}, "replicas": 2}"#,
+        ),
+        (
+            r#"{"a": [1, 2, 3, 4, 5, 6]}"#,
+            r#"{"a": [0, 2, 4, 5, 7, 8, 9]}"#,
+        ),
+        (r#"{"a": [1, 2, 3]}"#, r#"{"a": []}"#),
+        (
+            r#"{"items": [{"id": 1, "v": "a"}, {"id": 2, "v": "b"}, {"id": 3}]}"#,
+            r#"{"items": [{"id": 2, "v": "c"}, {"id": 3}, {"id": 4, "v": "d"}]}"#,
+        ),
+        (r#"{"a": [[1, 2], [3]]}"#, r#"{"a": [[2], [3, 4], []]}"#),
+    ];
+
+    for (i, (old, new)) in cases.into_iter().enumerate() {
+        check_patch(&format!("patch-{i}"), old, new, &[], new);
+    }
+
+    let output = run(
+        "empty",
+        &[("old.json", "{}")],
+        &["old.json", "old.json", "-f", "json-patch"],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "[]\n");
+}
+
+#[test]
+fn json_patch_leaves_ignored_elements_alone() {
+    check_patch(
+        "ignored-removal",
+        r#"{"a": [1, 2, 3, 4]}"#,
+        r#"{"a": [2, 3, 5]}"#,
+        &["-i", "a[0]"],
+        r#"{"a": [1, 2, 3, 5]}"#,
+    );
+    check_patch(
+        "ignored-update",
+        r#"{"a": [1, 2, 3]}"#,
+        r#"{"a": [9, 2, 4]}"#,
+        &["-i", "a[0]"],
+        r#"{"a": [1, 2, 4]}"#,
+    );
+    check_patch(
+        "ignored-addition",
+        r#"{"a": [1, 2]}"#,
+        r#"{"a": [1, 2, 7, 8]}"#,
+        &["-i", "a[2]"],
+        r#"{"a": [1, 2, 8]}"#,
+    );
+    check_patch(
+        "ignored-middle",
+        r#"{"a": [1, 2, 3, 4]}"#,
+        r#"{"a": [1, 3, 4, 9]}"#,
+        &["--ignore", "a[1]"],
+        r#"{"a": [1, 2, 3, 4, 9]}"#,
+    );
+}